@@ -0,0 +1,57 @@
+use std::io::{self, Write};
+
+///Writes difference between lines of `old` and `new`, prefixing them with `-`, `+` or space
+pub fn write_diff<O: Write>(old: &str, new: &str, out: &mut O) -> io::Result<()> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    //Length of longest common subsequence of old[idx..] and new[jdx..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for idx in (0..old.len()).rev() {
+        for jdx in (0..new.len()).rev() {
+            common[idx][jdx] = if old[idx] == new[jdx] {
+                common[idx + 1][jdx + 1] + 1
+            } else {
+                common[idx + 1][jdx].max(common[idx][jdx + 1])
+            };
+        }
+    }
+
+    let (mut idx, mut jdx) = (0, 0);
+    while idx < old.len() || jdx < new.len() {
+        if idx < old.len() && jdx < new.len() && old[idx] == new[jdx] {
+            writeln!(out, " {}", old[idx])?;
+            idx += 1;
+            jdx += 1;
+        } else if idx < old.len() && (jdx == new.len() || common[idx + 1][jdx] >= common[idx][jdx + 1]) {
+            writeln!(out, "-{}", old[idx])?;
+            idx += 1;
+        } else {
+            writeln!(out, "+{}", new[jdx])?;
+            jdx += 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_diff;
+
+    fn diff(old: &str, new: &str) -> String {
+        let mut out = Vec::new();
+        write_diff(old, new, &mut out).expect("to write diff");
+        String::from_utf8(out).expect("valid utf-8")
+    }
+
+    #[test]
+    fn should_write_line_diff() {
+        assert_eq!(diff("a\nb\nc", "a\nb\nc"), " a\n b\n c\n");
+        assert_eq!(diff("", "a\nb"), "+a\n+b\n");
+        assert_eq!(diff("a\nb", ""), "-a\n-b\n");
+        //Deletions are written before additions of the same place
+        assert_eq!(diff("a\nb\nc\nd", "a\nx\nc\nd\ne"), " a\n-b\n+x\n c\n d\n+e\n");
+        //Common lines are kept by longest subsequence rather than first match
+        assert_eq!(diff("x\na\nb\nc", "a\nb\nc\nx"), "-x\n a\n b\n c\n+x\n");
+    }
+}
@@ -0,0 +1,212 @@
+//!Command line tool to print outputs of structs deriving `Shema` without compiling crate
+//!
+//!Sources are parsed by the same code as derive macro, hence outputs are identical to generated constants.
+
+#[path = "../../src/lib.rs"]
+mod derive;
+mod outputs;
+mod diff;
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+const USAGE: &str = "Usage:
+  shema dump [--struct <NAME>] [--format <FORMAT>] [PATH...]
+  shema diff [--struct <NAME>] [--format <FORMAT>] <FROM> <TO> [PATH...]
+
+Arguments:
+  PATH     Rust source file or directory to search recursively (default: src)
+  FROM/TO  Git revisions to compare outputs of
+
+Options:
+  --struct <NAME>    Selects outputs of struct by its name
+  --format <FORMAT>  Selects output by name of its constant with or without `SHEMA_` prefix (e.g. firehose_schema, athena_ddl)
+";
+
+struct Args {
+    command: String,
+    struct_name: Option<String>,
+    format: Option<String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = std::env::args().skip(1);
+        let command = args.next().ok_or_else(|| "Missing command".to_owned())?;
+        let mut result = Self {
+            command,
+            struct_name: None,
+            format: None,
+            positional: Vec::new(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--struct" => result.struct_name = Some(args.next().ok_or_else(|| "'--struct' requires value".to_owned())?),
+                "--format" => {
+                    let format = args.next().ok_or_else(|| "'--format' requires value".to_owned())?.to_uppercase();
+                    result.format = Some(match format.starts_with("SHEMA_") {
+                        true => format,
+                        false => format!("SHEMA_{format}"),
+                    });
+                },
+                unknown if unknown.starts_with("--") => return Err(format!("Unknown option '{unknown}'")),
+                _ => result.positional.push(arg),
+            }
+        }
+        Ok(result)
+    }
+
+    #[inline]
+    fn is_selected(&self, key: &str) -> bool {
+        let (struct_name, name) = key.split_once("::").expect("to have struct name");
+        self.struct_name.as_deref().is_none_or(|expected| expected == struct_name) && self.format.as_deref().is_none_or(|expected| expected == name)
+    }
+
+    ///Evaluates outputs, keeping only selected ones
+    fn select(&self, mut outputs: outputs::Outputs) -> Result<BTreeMap<String, String>, Vec<String>> {
+        let mut result = outputs.evaluate();
+        if !outputs.errors.is_empty() {
+            return Err(outputs.errors);
+        }
+        result.retain(|key, _| self.is_selected(key));
+        Ok(result)
+    }
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_owned());
+        return Ok(());
+    }
+
+    let mut entries = std::fs::read_dir(path)?.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|extension| extension == "rs") {
+            collect_files(&entry, files)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_sources(paths: &[String]) -> Result<outputs::Outputs, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_files(Path::new(path), &mut files).map_err(|error| format!("{path}: {error}"))?;
+    }
+
+    let mut outputs = outputs::Outputs::default();
+    for file in files {
+        let source = std::fs::read_to_string(&file).map_err(|error| format!("{}: {error}", file.display()))?;
+        outputs.add_source(&file.display().to_string(), &source);
+    }
+    Ok(outputs)
+}
+
+fn git(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git").args(args).output().map_err(|error| format!("Unable to run git: {error}"))?;
+    if !output.status.success() {
+        return Err(format!("git {}: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    String::from_utf8(output.stdout).map_err(|_| format!("git {}: output is not valid utf-8", args.join(" ")))
+}
+
+fn read_git_sources(revision: &str, paths: &[String]) -> Result<outputs::Outputs, String> {
+    let mut args = vec!["ls-tree", "-r", "--name-only", revision, "--"];
+    args.extend(paths.iter().map(String::as_str));
+    let files = git(&args)?;
+
+    let mut outputs = outputs::Outputs::default();
+    for file in files.lines().filter(|file| file.ends_with(".rs")) {
+        //Paths of ls-tree are relative to current directory
+        let source = git(&["show", &format!("{revision}:./{file}")])?;
+        outputs.add_source(&format!("{revision}:{file}"), &source);
+    }
+    Ok(outputs)
+}
+
+fn dump(args: &Args) -> Result<bool, Vec<String>> {
+    let paths = match args.positional.is_empty() {
+        true => vec!["src".to_owned()],
+        false => args.positional.clone(),
+    };
+    let outputs = args.select(read_sources(&paths).map_err(|error| vec![error])?)?;
+
+    let mut out = io::stdout().lock();
+    //Single output is printed as it is, to be redirected into file
+    if outputs.len() == 1 {
+        let _ = writeln!(out, "{}", outputs.values().next().expect("to have output"));
+        return Ok(true);
+    }
+    for (key, value) in outputs.iter() {
+        let _ = writeln!(out, "--- {key}\n{value}\n");
+    }
+    Ok(true)
+}
+
+fn diff(args: &Args) -> Result<bool, Vec<String>> {
+    let (from, to, paths) = match args.positional.as_slice() {
+        [from, to, paths @ ..] => (from, to, paths),
+        _ => return Err(vec!["'diff' requires revisions to compare".to_owned()]),
+    };
+    let paths = match paths.is_empty() {
+        true => vec!["src".to_owned()],
+        false => paths.to_vec(),
+    };
+    let old = args.select(read_git_sources(from, &paths).map_err(|error| vec![error])?)?;
+    let new = args.select(read_git_sources(to, &paths).map_err(|error| vec![error])?)?;
+
+    let mut is_same = true;
+    let mut out = io::stdout().lock();
+    let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let (old, new) = (old.get(key), new.get(key));
+        if old == new {
+            continue;
+        }
+        is_same = false;
+        let _ = writeln!(out, "--- {from} {key}\n+++ {to} {key}");
+        let _ = diff::write_diff(old.map_or("", String::as_str), new.map_or("", String::as_str), &mut out);
+    }
+    Ok(is_same)
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match args.command.as_str() {
+        "dump" => dump(&args),
+        "diff" => diff(&args),
+        "help" | "--help" | "-h" => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        },
+        unknown => {
+            eprintln!("Unknown command '{unknown}'\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        //Outputs differ
+        Ok(false) => ExitCode::FAILURE,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{error}");
+            }
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::derive;
+
+//Constant of generated impl
+struct Const {
+    is_hidden: bool,
+    expr: syn::Expr,
+}
+
+///Outputs of structs deriving `Shema`
+#[derive(Default)]
+pub struct Outputs {
+    //Struct's name to its constants
+    consts: HashMap<String, Vec<(String, Const)>>,
+    pub errors: Vec<String>,
+}
+
+#[inline]
+fn is_shema_derive(attr: &syn::Attribute) -> bool {
+    if !attr.path().is_ident("derive") {
+        return false;
+    }
+    let mut is_shema = false;
+    let _ = attr.parse_nested_meta(|meta| {
+        is_shema |= meta.path.segments.last().is_some_and(|segment| segment.ident == "Shema");
+        Ok(())
+    });
+    is_shema
+}
+
+impl Outputs {
+    ///Parses source file, generating code of every struct deriving `Shema` as macro does
+    pub fn add_source(&mut self, path: &str, source: &str) {
+        match syn::parse_file(source) {
+            Ok(file) => self.add_items(path, &file.items),
+            Err(error) => self.errors.push(format!("{path}: {error}")),
+        }
+    }
+
+    fn add_items(&mut self, path: &str, items: &[syn::Item]) {
+        for item in items {
+            match item {
+                syn::Item::Struct(item) if item.attrs.iter().any(is_shema_derive) => {
+                    let payload = syn::DataStruct {
+                        struct_token: item.struct_token,
+                        fields: item.fields.clone(),
+                        semi_token: item.semi_token,
+                    };
+                    let code = match derive::from_struct(&item.attrs, &item.ident, &item.generics, &payload) {
+                        Ok(code) => code,
+                        Err(error) => {
+                            self.errors.push(format!("{path}: {}: {error}", item.ident));
+                            continue;
+                        }
+                    };
+                    match syn::parse_file(&code) {
+                        Ok(code) => self.add_generated(path, &item.ident.to_string(), &code),
+                        Err(error) => self.errors.push(format!("{path}: {}: generated invalid code: {error}", item.ident)),
+                    }
+                },
+                syn::Item::Mod(syn::ItemMod { content: Some((_, items)), .. }) => self.add_items(path, items),
+                _ => (),
+            }
+        }
+    }
+
+    fn add_generated(&mut self, path: &str, name: &str, code: &syn::File) {
+        //Nested struct is referred by its name only, hence it must be unique
+        if self.consts.contains_key(name) {
+            self.errors.push(format!("{path}: {name}: struct with the same name already derives Shema"));
+            return;
+        }
+
+        let mut consts = Vec::new();
+        for item in code.items.iter() {
+            let item = match item {
+                syn::Item::Impl(item) if item.trait_.is_none() => item,
+                _ => continue,
+            };
+            for item in item.items.iter() {
+                if let syn::ImplItem::Const(item) = item {
+                    let is_hidden = item.attrs.iter().any(|attr| attr.path().is_ident("doc") && attr.meta.require_list().is_ok_and(|list| list.tokens.to_string() == "hidden"));
+                    consts.push((item.ident.to_string(), Const {
+                        is_hidden,
+                        expr: item.expr.clone(),
+                    }));
+                }
+            }
+        }
+        self.consts.insert(name.to_owned(), consts);
+    }
+
+    ///Evaluates public constants of structs, keyed as `<Struct>::<CONST>`
+    pub fn evaluate(&mut self) -> BTreeMap<String, String> {
+        let mut result = BTreeMap::new();
+        let mut errors = Vec::new();
+        for (name, consts) in self.consts.iter() {
+            for (const_name, value) in consts.iter().filter(|(_, value)| !value.is_hidden) {
+                match self.evaluate_expr(name, &value.expr) {
+                    Ok(value) => {
+                        result.insert(format!("{name}::{const_name}"), value);
+                    },
+                    Err(error) => errors.push(format!("{name}::{const_name}: {error}")),
+                }
+            }
+        }
+        self.errors.extend(errors);
+        result
+    }
+
+    fn resolve(&self, owner: &str, name: &str) -> Result<String, String> {
+        let consts = match self.consts.get(owner) {
+            Some(consts) => consts,
+            None => return Err(format!("struct '{owner}' deriving Shema is not found")),
+        };
+        match consts.iter().find(|(const_name, _)| const_name == name) {
+            Some((_, value)) => self.evaluate_expr(owner, &value.expr),
+            None => Err(format!("'{owner}' has no constant '{name}'")),
+        }
+    }
+
+    fn evaluate_expr(&self, owner: &str, expr: &syn::Expr) -> Result<String, String> {
+        match expr {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(value), .. }) => Ok(value.value()),
+            //`Self::CONST` or `<Nested>::CONST`
+            syn::Expr::Path(path) => {
+                let name = path.path.segments.last().expect("to have const name").ident.to_string();
+                match path.qself.as_ref().map(|qself| &*qself.ty) {
+                    Some(syn::Type::Path(ty)) => {
+                        let nested = ty.path.segments.last().expect("to have type name").ident.to_string();
+                        self.resolve(&nested, &name)
+                    },
+                    Some(_) => Err("unexpected type of constant".to_owned()),
+                    None => self.resolve(owner, &name),
+                }
+            },
+            //Concatenation of parts, as it is done by `utils::write_const_str`
+            syn::Expr::Block(block) => {
+                let parts = block.block.stmts.iter().find_map(|stmt| match stmt {
+                    syn::Stmt::Item(syn::Item::Const(item)) if item.ident == "PARTS" => Some(&*item.expr),
+                    _ => None,
+                });
+                let parts = match parts {
+                    Some(syn::Expr::Reference(parts)) => match &*parts.expr {
+                        syn::Expr::Array(parts) => parts,
+                        _ => return Err("unexpected constant parts".to_owned()),
+                    },
+                    _ => return Err("unexpected constant expression".to_owned()),
+                };
+
+                let mut result = String::new();
+                for part in parts.elems.iter() {
+                    let (value, indent) = match part {
+                        syn::Expr::Tuple(part) if part.elems.len() == 2 => (&part.elems[0], &part.elems[1]),
+                        _ => return Err("unexpected constant part".to_owned()),
+                    };
+                    let indent = match indent {
+                        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(indent), .. }) => indent.base10_parse::<usize>().map_err(|error| error.to_string())?,
+                        _ => return Err("unexpected indentation of constant part".to_owned()),
+                    };
+                    let value = self.evaluate_expr(owner, value)?;
+                    //Every new line, except trailing one, is followed by indentation
+                    for (idx, ch) in value.char_indices() {
+                        result.push(ch);
+                        if ch == '\n' && idx + 1 < value.len() {
+                            result.extend(core::iter::repeat_n(' ', indent));
+                        }
+                    }
+                }
+                Ok(result)
+            },
+            _ => Err("unexpected constant expression".to_owned()),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SOURCE: &str = r#"
+#[derive(Shema)]
+#[shema(firehose_schema)]
+struct Location {
+    lat: f64,
+}
+
+mod events {
+    #[derive(Debug, shema::Shema)]
+    #[shema(firehose_schema, postgres)]
+    struct Event {
+        #[shema(index)]
+        client_id: String,
+        #[shema(nested)]
+        location: Option<Location>,
+    }
+}
+"#;
+
+//Directory removed once test is finished, even if it fails
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("shema-cli-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("to create temporary directory");
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn shema(dir: &Path, args: &[&str]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_shema")).current_dir(dir).args(args).output().expect("to run shema");
+    (output.status.code().expect("to exit"), String::from_utf8(output.stdout).expect("valid utf-8"))
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git").current_dir(dir).args(["-c", "user.name=shema", "-c", "user.email=shema@localhost", "-c", "commit.gpgsign=false"]).args(args).status().expect("to run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn should_dump_outputs() {
+    let dir = TempDir::new("dump");
+    std::fs::write(dir.0.join("source.rs"), SOURCE).expect("to write source");
+
+    let (code, output) = shema(&dir.0, &["dump", "--struct", "Event", "--format", "firehose_schema", "source.rs"]);
+    assert_eq!(code, 0);
+    assert_eq!(output, r#"{
+  "name": "event",
+  "partition_keys": [
+    {
+      "name": "client_id",
+      "type": "string",
+      "comment": "",
+      "mapping": ".client_id"
+    }
+  ],
+  "columns": [
+    {
+      "name": "location",
+      "type": "struct<lat:double>",
+      "comment": ""
+    }
+  ]
+}
+"#);
+
+    let (code, output) = shema(&dir.0, &["dump", "--format", "SHEMA_TABLE_NAME", "source.rs"]);
+    assert_eq!(code, 0);
+    assert_eq!(output, "--- Event::SHEMA_TABLE_NAME\nevent\n\n--- Location::SHEMA_TABLE_NAME\nlocation\n\n");
+
+    let (code, _) = shema(&dir.0, &["dump", "--format", "firehose_schema", "missing.rs"]);
+    assert_eq!(code, 2);
+
+    //Struct of the same name is ambiguous as nested type
+    std::fs::write(dir.0.join("duplicate.rs"), "#[derive(Shema)]\nstruct Location {\n    name: String,\n}\n").expect("to write source");
+    let (code, output) = shema(&dir.0, &["dump", "source.rs", "duplicate.rs"]);
+    assert_eq!(code, 2);
+    assert_eq!(output, "");
+}
+
+#[test]
+fn should_diff_outputs_of_revisions() {
+    let dir = TempDir::new("diff");
+    std::fs::create_dir(dir.0.join("src")).expect("to create src");
+    git(&dir.0, &["init", "--quiet"]);
+
+    let source = |fields: &str| format!("#[derive(Shema)]\n#[shema(postgres)]\nstruct Event {{\n{fields}}}\n");
+    std::fs::write(dir.0.join("src/lib.rs"), source("    client_id: String,\n")).expect("to write source");
+    git(&dir.0, &["add", "src"]);
+    git(&dir.0, &["commit", "--quiet", "-m", "first"]);
+    std::fs::write(dir.0.join("src/lib.rs"), source("    client_id: String,\n    count: Option<u32>,\n")).expect("to write source");
+    git(&dir.0, &["commit", "--quiet", "-a", "-m", "second"]);
+
+    let (code, output) = shema(&dir.0, &["diff", "HEAD~1", "HEAD"]);
+    assert_eq!(code, 1);
+    assert_eq!(output, r#"--- HEAD~1 Event::SHEMA_POSTGRES_DDL
++++ HEAD Event::SHEMA_POSTGRES_DDL
+ CREATE TABLE IF NOT EXISTS "event" (
+-    "client_id" text NOT NULL
++    "client_id" text NOT NULL,
++    "count" bigint
+ );
+"#);
+
+    let (code, output) = shema(&dir.0, &["diff", "HEAD", "HEAD", "src"]);
+    assert_eq!(code, 0);
+    assert_eq!(output, "");
+
+    let (code, _) = shema(&dir.0, &["diff", "HEAD", "missing-revision"]);
+    assert_eq!(code, 2);
+}
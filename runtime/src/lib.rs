@@ -0,0 +1,82 @@
+//!Runtime interface of structs derived by [shema](https://crates.io/crates/shema)
+//!
+//!Enabled via `#[shema(runtime)]`, allowing to write generic exporters and tooling over any derived struct.
+//!
+//!```rust
+//!use shema_runtime::{Shema, FieldKind};
+//!
+//!fn columns<T: Shema>() -> impl Iterator<Item = &'static str> {
+//!    T::FIELDS.iter().filter(|field| !matches!(field.kind, FieldKind::Struct)).map(|field| field.name)
+//!}
+//!```
+
+#![no_std]
+#![warn(missing_docs)]
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+///Kind of field as seen by schema generation
+pub enum FieldKind {
+    ///`i8`
+    Byte,
+    ///`i16`
+    Short,
+    ///`i32`
+    Integer,
+    ///`i64`
+    Long,
+    ///`u8`
+    UByte,
+    ///`u16`
+    UShort,
+    ///`u32`
+    UInteger,
+    ///`u64`
+    ULong,
+    ///`u64` marked as `decimal`
+    Decimal,
+    ///`f32`
+    Float,
+    ///`f64`
+    Double,
+    ///String
+    String,
+    ///`bool`
+    Boolean,
+    ///Timestamp with timezone
+    TimestampZ,
+    ///Sequence collection
+    Array,
+    ///Map collection or field marked as `json`
+    Object,
+    ///Field marked as `enumeration`
+    Enum,
+    ///Field marked as `nested`
+    Struct,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+///Description of struct's field
+pub struct FieldInfo {
+    ///Name of field within schema (i.e. after `rename`)
+    pub name: &'static str,
+    ///Name of field within Rust struct
+    pub rust_name: &'static str,
+    ///Kind of field
+    pub kind: FieldKind,
+    ///Field is `Option`
+    pub optional: bool,
+    ///Field is marked as `index`
+    pub index: bool,
+    ///Field is marked as `firehose_date_index`
+    pub date_index: bool,
+    ///Field's documentation or empty string
+    pub doc: &'static str,
+}
+
+///Schema of struct derived by `shema`
+pub trait Shema {
+    ///Table name in lower case, same as `SHEMA_TABLE_NAME`
+    const TABLE_NAME: &'static str;
+    ///Fields in order of declaration
+    const FIELDS: &'static [FieldInfo];
+}
@@ -0,0 +1,289 @@
+use core::fmt;
+
+use super::{TableSchema, Field, FieldType, FieldFlag, TimestampEncoding};
+
+impl TimestampEncoding {
+    #[inline(always)]
+    fn arrow_time_unit(&self) -> &'static str {
+        match self {
+            Self::Millis => "Millisecond",
+            Self::Micros => "Microsecond",
+            Self::Nanos => "Nanosecond",
+            Self::Int96 => unreachable!("int96 timestamp is rejected for arrow_code"),
+        }
+    }
+}
+
+#[inline(always)]
+fn is_partition_key(field: &Field) -> bool {
+    field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex)
+}
+
+//Arrow's counterpart of parquet primitive type
+struct ArrowPrimitive {
+    typ: FieldType,
+    timestamp: TimestampEncoding,
+}
+
+impl ArrowPrimitive {
+    #[inline]
+    fn new(field: &Field, typ: FieldType) -> Self {
+        Self {
+            typ,
+            timestamp: field.timestamp,
+        }
+    }
+
+    #[inline]
+    fn data_type(&self) -> String {
+        let data_type = match self.typ {
+            FieldType::Byte => "Int8",
+            FieldType::Short => "Int16",
+            FieldType::Integer => "Int32",
+            FieldType::Long => "Int64",
+            FieldType::UByte => "UInt8",
+            FieldType::UShort => "UInt16",
+            FieldType::UInteger => "UInt32",
+            FieldType::ULong => "UInt64",
+            //Written as FIXED_LEN_BYTE_ARRAY(9) by parquet
+            FieldType::Decimal => "Decimal128(20, 0)",
+            FieldType::Float => "Float32",
+            FieldType::Double => "Float64",
+            FieldType::Boolean => "Boolean",
+            FieldType::TimestampZ => return format!("::arrow_schema::DataType::Timestamp(::arrow_schema::TimeUnit::{}, Some(\"UTC\".into()))", self.timestamp.arrow_time_unit()),
+            //Encode all arrays/objects as strings
+            FieldType::String | FieldType::Array | FieldType::Object | FieldType::Enum => "Utf8",
+            FieldType::Struct => unreachable!("nested struct's type is declared by its own derive"),
+        };
+        format!("::arrow_schema::DataType::{data_type}")
+    }
+
+    #[inline]
+    fn builder(&self) -> String {
+        let builder = match self.typ {
+            FieldType::Byte => "Int8Builder",
+            FieldType::Short => "Int16Builder",
+            FieldType::Integer => "Int32Builder",
+            FieldType::Long => "Int64Builder",
+            FieldType::UByte => "UInt8Builder",
+            FieldType::UShort => "UInt16Builder",
+            FieldType::UInteger => "UInt32Builder",
+            FieldType::ULong => "UInt64Builder",
+            FieldType::Decimal => return "::arrow_array::builder::Decimal128Builder::new().with_precision_and_scale(20, 0)?".to_owned(),
+            FieldType::Float => "Float32Builder",
+            FieldType::Double => "Float64Builder",
+            FieldType::Boolean => "BooleanBuilder",
+            FieldType::TimestampZ => return format!("::arrow_array::builder::Timestamp{}Builder::new().with_timezone(\"UTC\")", self.timestamp.arrow_time_unit()),
+            FieldType::String | FieldType::Array | FieldType::Object | FieldType::Enum => "StringBuilder",
+            FieldType::Struct => unreachable!("nested struct is built by its own derive"),
+        };
+        format!("::arrow_array::builder::{builder}::new()")
+    }
+}
+
+//Writes expression converting `value` reference into builder's value
+impl fmt::Display for ArrowPrimitive {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.typ {
+            FieldType::Byte => fmt.write_str("*value as i8"),
+            FieldType::Short => fmt.write_str("*value as i16"),
+            FieldType::Integer => fmt.write_str("*value as i32"),
+            FieldType::Long => fmt.write_str("*value as i64"),
+            FieldType::UByte => fmt.write_str("*value as u8"),
+            FieldType::UShort => fmt.write_str("*value as u16"),
+            FieldType::UInteger => fmt.write_str("*value as u32"),
+            FieldType::ULong => fmt.write_str("*value as u64"),
+            FieldType::Decimal => fmt.write_str("*value as i128"),
+            FieldType::Float => fmt.write_str("*value as f32"),
+            FieldType::Double => fmt.write_str("*value as f64"),
+            FieldType::Boolean => fmt.write_str("*value"),
+            FieldType::String => fmt.write_str("value"),
+            FieldType::TimestampZ => fmt.write_fmt(format_args!(r#"match i64::try_from(value.unix_timestamp_nanos().div_euclid({unit_nanos})) {{
+                    Ok(timestamp) => timestamp,
+                    Err(_) => return Err(::arrow_schema::ArrowError::ComputeError("Timestamp cannot fit INT64".into())),
+                }}"#, unit_nanos=self.timestamp.unit_nanos())),
+            //Encode all arrays/objects as JSON strings
+            FieldType::Array | FieldType::Object | FieldType::Enum => fmt.write_str(r#"match serde_json::to_string(value) {
+                    Ok(value) => value,
+                    Err(error) => return Err(::arrow_schema::ArrowError::ExternalError(Box::new(error))),
+                }"#),
+            FieldType::Struct => unreachable!("nested struct is built by its own derive"),
+        }
+    }
+}
+
+struct ArrowFieldSchema<'a>(&'a Field);
+
+impl fmt::Display for ArrowFieldSchema<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.0.table_field_name();
+        let nullable = self.0.typ_flags.is_type_flag(FieldFlag::Optional);
+        if let Some(nested) = self.0.nested.as_ref() {
+            return fmt.write_fmt(format_args!("::arrow_schema::Field::new(\"{name}\", ::arrow_schema::DataType::Struct(<{nested}>::shema_arrow_fields().into()), {nullable})"));
+        }
+        match self.0.element {
+            //Same layout as parquet's MAP
+            Some(element) if matches!(self.0.typ, FieldType::Object) => fmt.write_fmt(format_args!(
+                "::arrow_schema::Field::new(\"{name}\", ::arrow_schema::DataType::Map(::std::sync::Arc::new(::arrow_schema::Field::new(\"key_value\", ::arrow_schema::DataType::Struct(vec![::arrow_schema::Field::new(\"key\", ::arrow_schema::DataType::Utf8, false), ::arrow_schema::Field::new(\"value\", {value}, false)].into()), false)), false), {nullable})",
+                value = ArrowPrimitive::new(self.0, element).data_type()
+            )),
+            //Same layout as parquet's LIST
+            Some(element) => fmt.write_fmt(format_args!(
+                "::arrow_schema::Field::new(\"{name}\", ::arrow_schema::DataType::List(::std::sync::Arc::new(::arrow_schema::Field::new(\"element\", {element}, false))), {nullable})",
+                element = ArrowPrimitive::new(self.0, element).data_type()
+            )),
+            None => fmt.write_fmt(format_args!("::arrow_schema::Field::new(\"{name}\", {}, {nullable})", ArrowPrimitive::new(self.0, self.0.typ).data_type())),
+        }
+    }
+}
+
+struct ArrowFieldColumn<'a>(&'a Field);
+
+impl fmt::Display for ArrowFieldColumn<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field_name = self.0.original_name.as_str();
+        let value = if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
+            format!("record.and_then(|record| record.{field_name}.as_ref())")
+        } else {
+            format!("record.map(|record| &record.{field_name})")
+        };
+
+        if let Some(nested) = self.0.nested.as_ref() {
+            return fmt.write_fmt(format_args!(r#"
+        let nested = records.iter().map(|record| {value}).collect::<Vec<_>>();
+        let nulls = ::arrow_array::BooleanArray::from(nested.iter().map(Option::is_some).collect::<Vec<_>>()).values().clone().into();
+        columns.push(::std::sync::Arc::new(::arrow_array::StructArray::try_new(<{nested}>::shema_arrow_fields().into(), <{nested}>::shema_arrow_columns(&nested)?, Some(nulls))?));"#));
+        }
+
+        let (builder, append) = match self.0.element {
+            Some(element) if matches!(self.0.typ, FieldType::Object) => {
+                let value = ArrowPrimitive::new(self.0, element);
+                (
+                    format!("::arrow_array::builder::MapBuilder::new(Some(::arrow_array::builder::MapFieldNames {{ entry: \"key_value\".into(), key: \"key\".into(), value: \"value\".into() }}), ::arrow_array::builder::StringBuilder::new(), {builder}).with_values_field(::arrow_schema::Field::new(\"value\", {data_type}, false))", builder=value.builder(), data_type=value.data_type()),
+                    format!(r#"for (key, value) in value.iter() {{
+                    builder.keys().append_value(key);
+                    builder.values().append_value({value});
+                }}
+                builder.append(true)?;"#),
+                )
+            },
+            Some(element) => {
+                let value = ArrowPrimitive::new(self.0, element);
+                (
+                    format!("::arrow_array::builder::ListBuilder::new({builder}).with_field(::arrow_schema::Field::new(\"element\", {data_type}, false))", builder=value.builder(), data_type=value.data_type()),
+                    format!(r#"for value in value.iter() {{
+                    builder.values().append_value({value});
+                }}
+                builder.append(true);"#),
+                )
+            },
+            None => {
+                let value = ArrowPrimitive::new(self.0, self.0.typ);
+                (value.builder(), format!("builder.append_value({value});"))
+            },
+        };
+        let append_null = if matches!(self.0.typ, FieldType::Object) && self.0.element.is_some() {
+            "builder.append(false)?;"
+        } else if self.0.element.is_some() {
+            "builder.append(false);"
+        } else {
+            "builder.append_null();"
+        };
+
+        fmt.write_fmt(format_args!(r#"
+        let mut builder = {builder};
+        for record in records.iter() {{
+            match {value} {{
+                Some(value) => {{
+                {append}
+                }},
+                None => {{ {append_null} }},
+            }}
+        }}
+        columns.push(::std::sync::Arc::new(builder.finish()));"#))
+    }
+}
+
+pub fn generate_arrow_code<O: fmt::Write>(schema: &TableSchema, out: &mut O) -> fmt::Result {
+    use super::TAB;
+
+    //schema
+    writeln!(out, "{TAB}#[doc(hidden)]")?;
+    writeln!(out, "{TAB}///Returns arrow schema's fields")?;
+    writeln!(out, "{TAB}pub fn shema_arrow_fields() -> Vec<::arrow_schema::Field> {{")?;
+    writeln!(out, "{TAB}{TAB}vec![")?;
+    for field in schema.fields.iter() {
+        if is_partition_key(field) {
+            //Partition keys are not written by Firehose data stream
+            continue;
+        }
+        writeln!(out, "{TAB}{TAB}{TAB}{},", ArrowFieldSchema(field))?;
+    }
+    writeln!(out, "{TAB}{TAB}]")?;
+    writeln!(out, "{TAB}}}\n")?;
+
+    writeln!(out, "{TAB}///Returns arrow schema, compatible with parquet schema")?;
+    writeln!(out, "{TAB}pub fn shema_arrow_schema() -> ::arrow_schema::Schema {{")?;
+    writeln!(out, "{TAB}{TAB}::arrow_schema::Schema::new(Self::shema_arrow_fields())")?;
+    writeln!(out, "{TAB}}}\n")?;
+
+    //columns
+    writeln!(out, "{TAB}#[doc(hidden)]")?;
+    writeln!(out, "{TAB}///Builds arrow columns of records, where `None` is record of null group")?;
+    write!(out, "{TAB}pub fn shema_arrow_columns(records: &[Option<&Self>]) -> ::core::result::Result<Vec<::arrow_array::ArrayRef>, ::arrow_schema::ArrowError> {{")?;
+    writeln!(out, "\n{TAB}{TAB}let mut columns = Vec::<::arrow_array::ArrayRef>::new();")?;
+    for field in schema.fields.iter() {
+        if is_partition_key(field) {
+            continue;
+        }
+        writeln!(out, "\n{TAB}{TAB}//write '{}' column{}", field.table_field_name(), ArrowFieldColumn(field))?;
+    }
+    writeln!(out, "\n{TAB}{TAB}Ok(columns)")?;
+    writeln!(out, "{TAB}}}\n")?;
+
+    //record batch
+    writeln!(out, "{TAB}///Converts records into arrow's record batch with `shema_arrow_schema`")?;
+    writeln!(out, "{TAB}pub fn shema_arrow_record_batch(records: &[Self]) -> ::core::result::Result<::arrow_array::RecordBatch, ::arrow_schema::ArrowError> {{")?;
+    writeln!(out, "{TAB}{TAB}let records = records.iter().map(Some).collect::<Vec<_>>();")?;
+    writeln!(out, "{TAB}{TAB}let options = ::arrow_array::RecordBatchOptions::new().with_row_count(Some(records.len()));")?;
+    writeln!(out, "{TAB}{TAB}::arrow_array::RecordBatch::try_new_with_options(::std::sync::Arc::new(Self::shema_arrow_schema()), Self::shema_arrow_columns(&records)?, &options)")?;
+    writeln!(out, "{TAB}}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::from_struct;
+
+    #[test]
+    fn should_reject_int96_timestamp() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            #[shema(arrow_code)]
+            struct Event {
+                name: String,
+                client_time: time::OffsetDateTime,
+            }
+        };
+        let payload = match &input.data {
+            syn::Data::Struct(payload) => payload,
+            _ => unreachable!(),
+        };
+        let error = from_struct(&input.attrs, &input.ident, &input.generics, payload).expect_err("to reject int96");
+        assert_eq!(error.to_string(), "'arrow_code' cannot write int96 timestamp of 'client_time'. Specify 'timestamp' as one of: millis, micros, nanos");
+
+        let input: syn::DeriveInput = syn::parse_quote! {
+            #[shema(arrow_code)]
+            struct Event {
+                #[shema(timestamp = "nanos")]
+                client_time: time::OffsetDateTime,
+            }
+        };
+        let payload = match &input.data {
+            syn::Data::Struct(payload) => payload,
+            _ => unreachable!(),
+        };
+        from_struct(&input.attrs, &input.ident, &input.generics, payload).expect("to accept nanos");
+    }
+}
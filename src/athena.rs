@@ -0,0 +1,98 @@
+use core::fmt::Write;
+
+use super::firehose::{self, FirehoseInput, FirehoseType};
+use super::PrefixStyle;
+use super::utils::{ConstExpr, SqlLiteral};
+
+const TAB: &'static str = "  ";
+
+#[derive(Default)]
+pub struct AthenaOptions {
+    //S3 location of table
+    pub location: Option<String>,
+    //Range of years for partition projection as `start,end`
+    pub projection: Option<(u16, u16)>,
+    //Extra table properties as key-value pairs
+    pub properties: Vec<(String, String)>,
+}
+
+fn write_columns(columns: &[FirehoseType<'_>], out: &mut String) {
+    for (idx, column) in columns.iter().enumerate() {
+        let _ = write!(out, "{TAB}`{}` {}", column.name, column.typ);
+        if !column.comment.is_empty() {
+            let _ = write!(out, " COMMENT {}", SqlLiteral(&column.comment));
+        }
+        if idx + 1 < columns.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+}
+
+pub fn generate_athena_ddl(input: FirehoseInput<'_>, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    let options = &input.schema.athena;
+    let schema = firehose::firehose_schema(input, consts);
+
+    let _ = writeln!(out, "CREATE EXTERNAL TABLE IF NOT EXISTS `{}` (", schema.name);
+    write_columns(&schema.columns, out);
+    out.push_str(")\n");
+
+    if !schema.partition_keys.is_empty() {
+        out.push_str("PARTITIONED BY (\n");
+        write_columns(&schema.partition_keys, out);
+        out.push_str(")\n");
+    }
+
+    out.push_str("STORED AS PARQUET");
+    if let Some(location) = options.location.as_ref() {
+        let _ = write!(out, "\nLOCATION {}", SqlLiteral(location));
+    }
+
+    let mut properties = Vec::new();
+    if let Some((start, end)) = options.projection {
+        properties.push(("projection.enabled".into(), "true".into()));
+        for key in schema.partition_keys.iter() {
+            let name = key.name;
+            if input.index_time_field.is_some() && input.schema.date_granularity.parts().contains(&name) {
+                //Firehose prefix pads month, day and hour with zero
+                let (range, digits) = match name {
+                    "year" => (format!("{start},{end}"), "4"),
+                    "month" => ("1,12".to_owned(), "2"),
+                    "day" => ("1,31".to_owned(), "2"),
+                    _ => ("0,23".to_owned(), "2"),
+                };
+                properties.push((format!("projection.{name}.type"), "integer".into()));
+                properties.push((format!("projection.{name}.range"), range));
+                properties.push((format!("projection.{name}.digits"), digits.into()));
+            } else {
+                //Arbitrary values must be specified within query
+                properties.push((format!("projection.{name}.type"), "injected".into()));
+            }
+        }
+
+        //Athena can only infer Hive layout
+        if let (PrefixStyle::Values, Some(location)) = (input.schema.prefix_style, options.location.as_ref()) {
+            let mut template = location.clone();
+            if !template.ends_with('/') {
+                template.push('/');
+            }
+            for key in schema.partition_keys.iter() {
+                let _ = write!(template, "${{{}}}/", key.name);
+            }
+            properties.push(("storage.location.template".into(), template));
+        }
+    }
+    properties.extend(options.properties.iter().cloned());
+
+    if !properties.is_empty() {
+        out.push_str("\nTBLPROPERTIES (\n");
+        for (idx, (key, value)) in properties.iter().enumerate() {
+            let _ = write!(out, "{TAB}{}={}", SqlLiteral(key), SqlLiteral(value));
+            if idx + 1 < properties.len() {
+                out.push(',');
+            }
+            out.push('\n');
+        }
+        out.push(')');
+    }
+}
@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use super::{TableSchema, Field, FieldType, FieldFlag};
+use super::utils::{self, ConstExpr};
+
+#[derive(serde_derive::Serialize)]
+#[serde(untagged)]
+enum AvroType<'a> {
+    Name(Cow<'a, str>),
+    Union(Vec<AvroType<'a>>),
+    Logical {
+        #[serde(rename = "type")]
+        typ: &'static str,
+        #[serde(rename = "logicalType")]
+        logical_type: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        precision: Option<u8>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        scale: Option<u8>,
+    },
+    Array {
+        #[serde(rename = "type")]
+        typ: &'static str,
+        items: Box<AvroType<'a>>,
+    },
+    Map {
+        #[serde(rename = "type")]
+        typ: &'static str,
+        values: Box<AvroType<'a>>,
+    },
+    Enum {
+        #[serde(rename = "type")]
+        typ: &'static str,
+        name: &'a str,
+        symbols: &'a [String],
+    },
+}
+
+#[derive(serde_derive::Serialize)]
+struct AvroField<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    typ: AvroType<'a>,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    doc: &'a str,
+    //Some(()) is serialized as null
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<()>,
+}
+
+#[derive(serde_derive::Serialize)]
+struct AvroRecord<'a> {
+    #[serde(rename = "type")]
+    typ: &'static str,
+    name: &'a str,
+    fields: Vec<AvroField<'a>>,
+}
+
+impl FieldType {
+    #[inline]
+    fn avro_type(&self) -> AvroType<'static> {
+        match self {
+            Self::Byte | Self::Short | Self::Integer | Self::UByte | Self::UShort => AvroType::Name("int".into()),
+            Self::Long | Self::UInteger | Self::ULong => AvroType::Name("long".into()),
+            Self::Decimal => AvroType::Logical {
+                typ: "bytes",
+                logical_type: "decimal",
+                precision: Some(20),
+                scale: Some(0),
+            },
+            Self::Float => AvroType::Name("float".into()),
+            Self::Double => AvroType::Name("double".into()),
+            Self::String => AvroType::Name("string".into()),
+            Self::Boolean => AvroType::Name("boolean".into()),
+            Self::TimestampZ => AvroType::Logical {
+                typ: "long",
+                logical_type: "timestamp-micros",
+                precision: None,
+                scale: None,
+            },
+            //Everything else is encoded as json string
+            Self::Array | Self::Object | Self::Enum => AvroType::Name("string".into()),
+            Self::Struct => unreachable!("nested struct's type is declared by its own derive"),
+        }
+    }
+}
+
+impl Field {
+    //Named types can be defined only once, hence `defined` holds names of types defined so far
+    fn avro_type<'a>(&'a self, defined: &mut HashSet<&'a str>, consts: &mut Vec<ConstExpr>) -> AvroType<'a> {
+        if let Some(nested) = self.nested.as_ref() {
+            //Record is referred by its name, once defined
+            let name = nested.rsplit("::").next().unwrap_or(nested).trim();
+            return if defined.insert(name) {
+                AvroType::Name(utils::const_placeholder(consts, format!("<{nested}>::SHEMA_AVRO_RECORD"), 0).into())
+            } else {
+                AvroType::Name(name.into())
+            };
+        }
+        if let Some(symbols) = self.symbols.as_ref() {
+            return if defined.insert(symbols.name.as_str()) {
+                AvroType::Enum {
+                    typ: "enum",
+                    name: symbols.name.as_str(),
+                    symbols: &symbols.values,
+                }
+            } else {
+                AvroType::Name(symbols.name.as_str().into())
+            };
+        }
+        match self.element {
+            Some(element) if matches!(self.typ, FieldType::Object) => AvroType::Map {
+                typ: "map",
+                values: Box::new(element.avro_type()),
+            },
+            Some(element) => AvroType::Array {
+                typ: "array",
+                items: Box::new(element.avro_type()),
+            },
+            None => self.typ.avro_type(),
+        }
+    }
+}
+
+///Generates avro's record schema using placeholders for nested records
+pub fn generate_avro_record(schema: &TableSchema, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    let mut defined = HashSet::new();
+    defined.insert(schema.name.as_str());
+
+    let mut record = AvroRecord {
+        typ: "record",
+        name: schema.name.as_str(),
+        fields: Vec::with_capacity(schema.fields.len()),
+    };
+    for field in schema.fields.iter() {
+        let mut typ = field.avro_type(&mut defined, consts);
+        let mut default = None;
+        if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            //Default value must be of the first type in union
+            typ = AvroType::Union(vec![AvroType::Name("null".into()), typ]);
+            default = Some(());
+        }
+        record.fields.push(AvroField {
+            name: field.table_field_name(),
+            typ,
+            doc: field.docstring.as_str(),
+            default,
+        });
+    }
+
+    let json = serde_json::to_string_pretty(&record).expect("to serialize avro schema");
+    utils::write_json_unquoting_consts(&json, out, consts);
+}
@@ -0,0 +1,255 @@
+use core::fmt::Write;
+use std::borrow::Cow;
+use std::path::Path;
+
+use super::{FieldFlag, glue_compat};
+use super::firehose::{self, FirehoseInput};
+use super::utils::{self, ConstExpr};
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+struct BaselineColumn<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(rename = "type", borrow)]
+    typ: Cow<'a, str>,
+    //Parquet's repetition, irrelevant for partition keys
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    required: Option<bool>,
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+struct Baseline<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    partition_keys: Vec<BaselineColumn<'a>>,
+    #[serde(borrow)]
+    columns: Vec<BaselineColumn<'a>>,
+}
+
+fn baseline<'a>(input: FirehoseInput<'a>, consts: &mut Vec<ConstExpr>) -> Baseline<'a> {
+    let schema = firehose::firehose_schema(input, consts);
+    //Columns are declared in order of fields, skipping partition keys
+    let fields = input.schema.fields.iter().filter(|field| !field.typ_flags.is_type_flag(FieldFlag::Index) || field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex));
+
+    Baseline {
+        name: schema.name.into(),
+        partition_keys: schema.partition_keys.into_iter().map(|key| BaselineColumn {
+            name: key.name.into(),
+            typ: key.typ,
+            required: None,
+        }).collect(),
+        columns: schema.columns.into_iter().zip(fields).map(|(column, field)| BaselineColumn {
+            name: column.name.into(),
+            typ: column.typ,
+            required: Some(!field.typ_flags.is_type_flag(FieldFlag::Optional)),
+        }).collect(),
+    }
+}
+
+///Generates snapshot of glue schema with parquet's repetition of columns to be committed as baseline
+pub fn generate_baseline(input: FirehoseInput<'_>, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    let baseline = baseline(input, consts);
+    //Placeholders of nested struct's types are within strings, hence left as they are
+    out.push_str(&serde_json::to_string_pretty(&baseline).expect("to serialize baseline"));
+}
+
+fn write_keys(keys: &[BaselineColumn<'_>]) -> String {
+    keys.iter().map(|key| format!("{}:{}", key.name, key.typ)).collect::<Vec<_>>().join(",")
+}
+
+//Returns description of every incompatible change, collecting nested columns to be checked once their type is known
+fn compare<'a>(stored: &'a Baseline<'_>, current: &'a Baseline<'_>, nested: &mut Vec<(&'a BaselineColumn<'a>, &'a BaselineColumn<'a>)>) -> Vec<String> {
+    let mut errors = Vec::new();
+    if stored.name != current.name {
+        errors.push(format!("table name changed from '{}' to '{}'", stored.name, current.name));
+    }
+
+    let (stored_keys, current_keys) = (write_keys(&stored.partition_keys), write_keys(&current.partition_keys));
+    if stored_keys != current_keys {
+        errors.push(format!("partition keys changed from '{stored_keys}' to '{current_keys}'"));
+    }
+
+    let mut is_removed = false;
+    for column in stored.columns.iter() {
+        let new_column = match current.columns.iter().find(|new_column| new_column.name == column.name) {
+            Some(new_column) => new_column,
+            None => {
+                is_removed = true;
+                errors.push(format!("column '{}' was removed", column.name));
+                continue;
+            }
+        };
+
+        if utils::has_const_placeholder(&new_column.typ) {
+            nested.push((column, new_column));
+        } else if !glue_compat::is_compatible_type(column.typ.as_bytes(), new_column.typ.as_bytes()) {
+            errors.push(format!("column '{}' changed type from '{}' to '{}'", column.name, column.typ, new_column.typ));
+        }
+
+        if column.required == Some(true) && new_column.required == Some(false) {
+            errors.push(format!("column '{}' changed from required to optional", column.name));
+        }
+    }
+
+    //Only appending columns keeps positions of existing ones
+    if !is_removed && stored.columns.iter().zip(current.columns.iter()).any(|(column, new_column)| column.name != new_column.name) {
+        errors.push("columns were reordered, while new columns can only be appended".to_owned());
+    }
+    errors
+}
+
+///Checks that schema can replace baseline stored at `path` without breaking existing data
+///
+///Returns description of every incompatible change.
+///Type of nested struct is only known at compile time, hence `out` is given code to check nested columns as constant.
+pub fn check_baseline(input: FirehoseInput<'_>, path: &Path, out: &mut String) -> Result<(), String> {
+    let stored = match std::fs::read_to_string(path) {
+        Ok(stored) => stored,
+        Err(error) => return Err(format!("Unable to read baseline '{}': {error}", path.display())),
+    };
+    let stored: Baseline<'_> = match serde_json::from_str(&stored) {
+        Ok(stored) => stored,
+        Err(error) => return Err(format!("Baseline '{}' is not valid: {error}", path.display())),
+    };
+    let mut consts = Vec::new();
+    let current = baseline(input, &mut consts);
+
+    let mut nested = Vec::new();
+    let errors = compare(&stored, &current, &mut nested);
+    if !errors.is_empty() {
+        return Err(format!("Schema is incompatible with baseline '{}': {}", path.display(), errors.join("; ")));
+    }
+
+    if !nested.is_empty() {
+        out.push_str("\nconst _: () = {\n");
+        out.push_str(include_str!("glue_compat.rs"));
+        for (column, new_column) in nested {
+            let error = format!("Schema is incompatible with baseline '{}': column '{}' changed type from '{}' to incompatible nested struct", path.display(), column.name, column.typ);
+            let _ = write!(out, "\nconst _: () = assert!(is_compatible_type({:?}.as_bytes(), (", column.typ);
+            utils::write_const_str(out, &new_column.typ, &consts);
+            let _ = writeln!(out, ").as_bytes()), {:?});", error.replace('{', "{{").replace('}', "}}"));
+        }
+        out.push_str("};\n");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Baseline, compare};
+    use super::super::glue_compat::is_compatible_type;
+
+    const STORED: &str = r#"{
+        "name": "event",
+        "partition_keys": [{"name": "client_id", "type": "string"}],
+        "columns": [
+            {"name": "client_time", "type": "timestamp", "required": true},
+            {"name": "count", "type": "int", "required": true},
+            {"name": "name", "type": "string", "required": false}
+        ]
+    }"#;
+
+    fn check(current: &str) -> Vec<String> {
+        let stored: Baseline<'_> = serde_json::from_str(STORED).expect("valid baseline");
+        let current: Baseline<'_> = serde_json::from_str(current).expect("valid baseline");
+        compare(&stored, &current, &mut Vec::new())
+    }
+
+    #[test]
+    fn should_accept_widened_and_appended_columns() {
+        let errors = check(r#"{
+            "name": "event",
+            "partition_keys": [{"name": "client_id", "type": "string"}],
+            "columns": [
+                {"name": "client_time", "type": "timestamp", "required": true},
+                {"name": "count", "type": "bigint", "required": true},
+                {"name": "name", "type": "string", "required": false},
+                {"name": "comment", "type": "string", "required": true}
+            ]
+        }"#);
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn should_reject_narrowed_type() {
+        let errors = check(r#"{
+            "name": "event",
+            "partition_keys": [{"name": "client_id", "type": "string"}],
+            "columns": [
+                {"name": "client_time", "type": "timestamp", "required": true},
+                {"name": "count", "type": "smallint", "required": true},
+                {"name": "name", "type": "string", "required": false}
+            ]
+        }"#);
+        assert_eq!(errors, ["column 'count' changed type from 'int' to 'smallint'"]);
+    }
+
+    #[test]
+    fn should_reject_removed_column() {
+        let errors = check(r#"{
+            "name": "event",
+            "partition_keys": [{"name": "client_id", "type": "string"}],
+            "columns": [
+                {"name": "client_time", "type": "timestamp", "required": true},
+                {"name": "name", "type": "string", "required": false}
+            ]
+        }"#);
+        assert_eq!(errors, ["column 'count' was removed"]);
+    }
+
+    #[test]
+    fn should_reject_reordered_columns() {
+        let errors = check(r#"{
+            "name": "event",
+            "partition_keys": [{"name": "client_id", "type": "string"}],
+            "columns": [
+                {"name": "client_time", "type": "timestamp", "required": true},
+                {"name": "name", "type": "string", "required": false},
+                {"name": "count", "type": "int", "required": true}
+            ]
+        }"#);
+        assert_eq!(errors, ["columns were reordered, while new columns can only be appended"]);
+    }
+
+    #[test]
+    fn should_reject_optional_column() {
+        let errors = check(r#"{
+            "name": "event",
+            "partition_keys": [{"name": "client_id", "type": "string"}],
+            "columns": [
+                {"name": "client_time", "type": "timestamp", "required": true},
+                {"name": "count", "type": "int", "required": false},
+                {"name": "name", "type": "string", "required": false}
+            ]
+        }"#);
+        assert_eq!(errors, ["column 'count' changed from required to optional"]);
+    }
+
+    #[test]
+    fn should_reject_changed_partition_keys() {
+        let errors = check(r#"{
+            "name": "event",
+            "partition_keys": [{"name": "client_id", "type": "string"}, {"name": "platform", "type": "string"}],
+            "columns": [
+                {"name": "client_time", "type": "timestamp", "required": true},
+                {"name": "count", "type": "int", "required": true},
+                {"name": "name", "type": "string", "required": false}
+            ]
+        }"#);
+        assert_eq!(errors, ["partition keys changed from 'client_id:string' to 'client_id:string,platform:string'"]);
+    }
+
+    #[test]
+    fn should_compare_nested_types_recursively() {
+        assert!(is_compatible_type(b"struct<lat:float,name:string>", b"struct<lat:double,name:string,tags:array<string>>"));
+        assert!(is_compatible_type(b"struct<inner:struct<id:int>,big:decimal(20,0)>", b"struct<inner:struct<id:bigint,name:string>,big:decimal(20,0)>"));
+        assert!(is_compatible_type(b"map<string,array<int>>", b"map<string,array<bigint>>"));
+
+        assert!(!is_compatible_type(b"struct<inner:struct<id:bigint>>", b"struct<inner:struct<id:int>>"));
+        assert!(!is_compatible_type(b"struct<inner:struct<id:int,name:string>>", b"struct<inner:struct<id:int>>"));
+        assert!(!is_compatible_type(b"struct<lat:double,name:string>", b"struct<name:string,lat:double>"));
+        assert!(!is_compatible_type(b"struct<lat:double>", b"string"));
+        assert!(!is_compatible_type(b"array<bigint>", b"array<int>"));
+    }
+}
@@ -0,0 +1,115 @@
+use super::{TableSchema, Field, FieldType, FieldFlag, DateGranularity};
+use super::utils::{self, ConstExpr};
+
+//BigQuery limits number of clustering columns
+pub const MAX_CLUSTERING_FIELDS: usize = 4;
+
+#[derive(serde_derive::Serialize)]
+struct BigQueryField<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    typ: &'static str,
+    mode: &'static str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    description: &'a str,
+    //Placeholder of nested struct's fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<String>,
+}
+
+#[derive(serde_derive::Serialize)]
+struct BigQueryTimePartitioning<'a> {
+    #[serde(rename = "type")]
+    typ: &'static str,
+    field: &'a str,
+}
+
+#[derive(serde_derive::Serialize)]
+struct BigQueryClustering<'a> {
+    fields: Vec<&'a str>,
+}
+
+impl FieldType {
+    #[inline(always)]
+    pub fn bigquery_type(&self) -> &'static str {
+        match self {
+            Self::Byte | Self::Short | Self::Integer | Self::Long | Self::UByte | Self::UShort | Self::UInteger | Self::ULong => "INT64",
+            //NUMERIC holds up to 29 integer digits
+            Self::Decimal => "NUMERIC",
+            Self::Float | Self::Double => "FLOAT64",
+            Self::String | Self::Enum => "STRING",
+            Self::Boolean => "BOOL",
+            Self::TimestampZ => "TIMESTAMP",
+            Self::Array | Self::Object => "JSON",
+            Self::Struct => "RECORD",
+        }
+    }
+}
+
+impl Field {
+    #[inline]
+    fn bigquery_type(&self) -> (&'static str, &'static str) {
+        let mode = if self.typ_flags.is_type_flag(FieldFlag::Optional) {
+            "NULLABLE"
+        } else {
+            "REQUIRED"
+        };
+        match self.element {
+            //BigQuery has no map type
+            Some(_) if matches!(self.typ, FieldType::Object) => ("JSON", mode),
+            //Repeated field cannot be null, hence null is written as empty array
+            Some(element) => (element.bigquery_type(), "REPEATED"),
+            None => (self.typ.bigquery_type(), mode),
+        }
+    }
+
+    #[inline]
+    ///Returns whether field can be used for clustering
+    pub fn is_bigquery_clustering_type(&self) -> bool {
+        !matches!(self.bigquery_type(), ("JSON" | "FLOAT64" | "RECORD", _) | (_, "REPEATED"))
+    }
+}
+
+///Generates BigQuery's list of fields using placeholders for nested records
+pub fn generate_bigquery_fields(schema: &TableSchema, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    let mut fields = Vec::with_capacity(schema.fields.len());
+    for field in schema.fields.iter() {
+        let (typ, mode) = field.bigquery_type();
+        fields.push(BigQueryField {
+            name: field.table_field_name(),
+            typ,
+            mode,
+            description: field.docstring.as_str(),
+            fields: field.nested.as_ref().map(|nested| utils::const_placeholder(consts, format!("<{nested}>::SHEMA_BIGQUERY_FIELDS"), 0)),
+        });
+    }
+
+    let json = serde_json::to_string_pretty(&fields).expect("to serialize bigquery schema");
+    utils::write_json_unquoting_consts(&json, out, consts);
+}
+
+///Generates `timePartitioning` of BigQuery's table by `firehose_date_index` with the same granularity
+pub fn generate_bigquery_time_partitioning(schema: &TableSchema) -> Option<String> {
+    let field = schema.index_time_field()?;
+    let partitioning = BigQueryTimePartitioning {
+        typ: match schema.date_granularity {
+            DateGranularity::Year => "YEAR",
+            DateGranularity::Month => "MONTH",
+            DateGranularity::Day => "DAY",
+            DateGranularity::Hour => "HOUR",
+        },
+        field: field.table_field_name(),
+    };
+    Some(serde_json::to_string_pretty(&partitioning).expect("to serialize bigquery time partitioning"))
+}
+
+///Generates `clustering` of BigQuery's table by `index` fields
+pub fn generate_bigquery_clustering(keys: &[&Field]) -> Option<String> {
+    if keys.is_empty() {
+        return None;
+    }
+    let clustering = BigQueryClustering {
+        fields: keys.iter().map(|field| field.table_field_name()).collect(),
+    };
+    Some(serde_json::to_string_pretty(&clustering).expect("to serialize bigquery clustering"))
+}
@@ -0,0 +1,93 @@
+use core::fmt::Write;
+use std::borrow::Cow;
+
+use super::{TableSchema, Field, FieldType, FieldFlag, DateGranularity};
+use super::utils::SqlLiteral;
+
+const TAB: &'static str = "    ";
+
+impl FieldType {
+    #[inline(always)]
+    pub fn clickhouse_type(&self) -> &'static str {
+        match self {
+            Self::Byte => "Int8",
+            Self::Short => "Int16",
+            Self::Integer => "Int32",
+            Self::Long => "Int64",
+            Self::UByte => "UInt8",
+            Self::UShort => "UInt16",
+            Self::UInteger => "UInt32",
+            //ClickHouse has native unsigned integers so there is no need for decimal
+            Self::ULong | Self::Decimal => "UInt64",
+            Self::Float => "Float32",
+            Self::Double => "Float64",
+            Self::String => "String",
+            Self::Boolean => "Bool",
+            Self::TimestampZ => "DateTime64(6, 'UTC')",
+            Self::Enum => "LowCardinality(String)",
+            //Everything else is stored as json string
+            Self::Array | Self::Object | Self::Struct => "String",
+        }
+    }
+}
+
+impl Field {
+    #[inline]
+    fn clickhouse_type(&self) -> Cow<'static, str> {
+        let is_optional = self.typ_flags.is_type_flag(FieldFlag::Optional);
+        match self.element {
+            //Collections cannot be Nullable, hence null is written as empty collection
+            Some(element) if matches!(self.typ, FieldType::Object) => format!("Map(String, {})", element.clickhouse_type()).into(),
+            Some(element) => format!("Array({})", element.clickhouse_type()).into(),
+            //LowCardinality must wrap Nullable
+            None if is_optional && matches!(self.typ, FieldType::Enum) => "LowCardinality(Nullable(String))".into(),
+            None if is_optional => format!("Nullable({})", self.typ.clickhouse_type()).into(),
+            None => self.typ.clickhouse_type().into(),
+        }
+    }
+}
+
+pub fn generate_clickhouse_ddl(schema: &TableSchema, out: &mut String) {
+    let _ = writeln!(out, "CREATE TABLE IF NOT EXISTS `{}` (", schema.lower_cased_table_name());
+    for (idx, field) in schema.fields.iter().enumerate() {
+        let _ = write!(out, "{TAB}`{}` {}", field.table_field_name(), field.clickhouse_type());
+        if !field.docstring.is_empty() {
+            let _ = write!(out, " COMMENT {}", SqlLiteral(&field.docstring));
+        }
+        if idx + 1 < schema.fields.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str(")\nENGINE = MergeTree");
+
+    if let Some(field) = schema.index_time_field() {
+        let function = match schema.date_granularity {
+            DateGranularity::Year => "toYear",
+            DateGranularity::Month => "toYYYYMM",
+            //Too many partitions hurt MergeTree, hence hourly data is still partitioned by day
+            DateGranularity::Day | DateGranularity::Hour => "toYYYYMMDD",
+        };
+        let _ = write!(out, "\nPARTITION BY {function}(`{}`)", field.table_field_name());
+    }
+
+    //Partition keys go first as they are most likely to be filtered by
+    let mut keys = schema.fields.iter().filter(|field| field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex)).collect::<Vec<_>>();
+    keys.extend(schema.index_time_field());
+    if keys.is_empty() {
+        out.push_str("\nORDER BY tuple()");
+    } else {
+        out.push_str("\nORDER BY (");
+        for (idx, field) in keys.iter().enumerate() {
+            if idx > 0 {
+                out.push_str(", ");
+            }
+            let _ = write!(out, "`{}`", field.table_field_name());
+        }
+        out.push(')');
+    }
+
+    if keys.iter().any(|field| field.typ_flags.is_type_flag(FieldFlag::Optional)) {
+        out.push_str("\nSETTINGS allow_nullable_key = 1");
+    }
+}
@@ -0,0 +1,49 @@
+use std::io;
+use std::path::Path;
+
+use super::utils::ConstExpr;
+
+///Outputs of struct to be written into files named `<table name>.<extension>`
+pub struct Exports {
+    files: Vec<(&'static str, String)>,
+    //Extensions of outputs that depend on nested struct's constants
+    unresolved: Vec<&'static str>,
+}
+
+impl Exports {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            unresolved: Vec::new(),
+        }
+    }
+
+    ///Adds output, unless its value is only known when generated code is compiled
+    pub fn add(&mut self, extension: &'static str, value: &str, consts: &[ConstExpr]) {
+        if consts.is_empty() {
+            self.files.push((extension, value.to_owned()));
+        } else {
+            self.unresolved.push(extension);
+        }
+    }
+
+    #[inline(always)]
+    ///Returns extensions of outputs that cannot be exported
+    pub fn unresolved(&self) -> &[&'static str] {
+        &self.unresolved
+    }
+
+    ///Writes outputs into `dir`, leaving files with the same content untouched
+    pub fn write(&self, dir: &Path, table_name: &str) -> io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        for (extension, value) in self.files.iter() {
+            let path = dir.join(format!("{table_name}.{extension}"));
+            match std::fs::read(&path) {
+                Ok(existing) if existing == value.as_bytes() => continue,
+                _ => std::fs::write(&path, value)?,
+            }
+        }
+        Ok(())
+    }
+}
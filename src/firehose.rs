@@ -1,7 +1,8 @@
 use std::{fmt, io};
 use std::borrow::Cow;
 
-use crate::{TAB, TableSchema, Field, FieldType, FieldFlag};
+use super::{TAB, TableSchema, Field, FieldType, FieldFlag, PartitionKey, PrefixStyle};
+use super::utils::{self, ConstExpr};
 
 impl FieldType {
     #[inline(always)]
@@ -12,6 +13,11 @@ impl FieldType {
             Self::Short => "smallint",
             Self::Integer => "int",
             Self::Long => "bigint",
+            //Unsigned integers are widened to the next signed type
+            Self::UByte => "smallint",
+            Self::UShort => "int",
+            Self::UInteger | Self::ULong => "bigint",
+            Self::Decimal => "decimal(20,0)",
             Self::Float => "float",
             Self::Double => "double",
             Self::String => "string",
@@ -19,10 +25,44 @@ impl FieldType {
             Self::TimestampZ => "timestamp",
             //Enout all arrays/objects as strings
             Self::Array | Self::Object | Self::Enum => "string",
+            Self::Struct => unreachable!("nested struct's type is declared by its own derive"),
         }
     }
 }
 
+impl Field {
+    #[inline]
+    ///Returns glue type, using placeholder for nested struct's type
+    fn aws_glue_type(&self, consts: &mut Vec<ConstExpr>) -> Cow<'static, str> {
+        if let Some(nested) = self.nested.as_ref() {
+            return utils::const_placeholder(consts, format!("<{nested}>::SHEMA_GLUE_TYPE"), 0).into();
+        }
+        match self.element {
+            Some(element) if matches!(self.typ, FieldType::Object) => format!("map<string,{}>", element.aws_glue_type()).into(),
+            Some(element) => format!("array<{}>", element.aws_glue_type()).into(),
+            None => self.typ.aws_glue_type().into(),
+        }
+    }
+}
+
+///Generates glue's `struct<...>` type of schema to be used when schema is nested within another
+pub fn generate_glue_struct_type(schema: &TableSchema, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    out.push_str("struct<");
+    for field in schema.fields.iter() {
+        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
+            //Partition keys are not written by Firehose data stream
+            continue;
+        }
+        if !out.ends_with('<') {
+            out.push(',');
+        }
+        out.push_str(field.table_field_name());
+        out.push(':');
+        out.push_str(&field.aws_glue_type(consts));
+    }
+    out.push('>');
+}
+
 #[derive(Copy, Clone)]
 pub struct FirehoseInput<'a> {
     pub schema: &'a TableSchema,
@@ -30,86 +70,228 @@ pub struct FirehoseInput<'a> {
 }
 
 #[derive(serde_derive::Serialize)]
-struct FirehoseType<'a> {
-    name: &'a str,
+pub struct FirehoseType<'a> {
+    pub name: &'a str,
     #[serde(rename = "type")]
-    typ: &'a str,
-    comment: Cow<'a, str>,
+    pub typ: Cow<'a, str>,
+    pub comment: Cow<'a, str>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    mapping: Option<String>,
+    pub mapping: Option<String>,
 }
 
 #[derive(serde_derive::Serialize)]
-struct FirehoseSchema<'a> {
-    name: String,
-    partition_keys: Vec<FirehoseType<'a>>,
-    columns: Vec<FirehoseType<'a>>,
+pub struct FirehoseSchema<'a> {
+    pub name: String,
+    pub partition_keys: Vec<FirehoseType<'a>>,
+    pub columns: Vec<FirehoseType<'a>>,
 }
 
-pub fn generate_firehose_schema<O: io::Write>(FirehoseInput { schema, index_time_field }: FirehoseInput<'_>, out: &mut O) -> io::Result<()> {
+pub fn generate_firehose_schema<O: io::Write>(input: FirehoseInput<'_>, out: &mut O, consts: &mut Vec<ConstExpr>) -> io::Result<()> {
+    let out_schema = firehose_schema(input, consts);
+    serde_json::to_writer_pretty(&mut *out, &out_schema).map_err(|error| io::Error::other(error))?;
+    out.flush()
+}
+
+///Builds glue table's partition keys and columns as they are declared for firehose
+pub fn firehose_schema<'a>(FirehoseInput { schema, index_time_field }: FirehoseInput<'a>, consts: &mut Vec<ConstExpr>) -> FirehoseSchema<'a> {
     let mut out_schema = FirehoseSchema {
         name: schema.lower_cased_table_name(),
         partition_keys: Vec::new(),
         columns: Vec::new(),
     };
 
-    if let Some(field) =  index_time_field {
-        let name = field.table_field_name();
-        let comment = format!("Extracted from '{name}'");
-        out_schema.partition_keys.push(FirehoseType {
-            name: "year",
-            typ: "string",
-            comment: comment.clone().into(),
-            mapping: Some(format!("(.{name}|split(\"-\")[0])")),
-        });
-        out_schema.partition_keys.push(FirehoseType {
-            name: "month",
-            typ: "string",
-            comment: comment.clone().into(),
-            mapping: Some(format!("(.{name}|split(\"-\")[1])")),
-        });
-        out_schema.partition_keys.push(FirehoseType {
-            name: "day",
-            typ: "string",
-            comment: comment.into(),
-            mapping: Some(format!("(.{name}|split(\"-\")[2]|split(\"T\")[0])")),
-        });
+    for key in schema.partition_keys.iter() {
+        match key {
+            PartitionKey::Date => {
+                let name = index_time_field.expect("date partition to have date index").table_field_name();
+                let comment = format!("Extracted from '{name}'");
+                for part in schema.date_granularity.parts() {
+                    //Timestamp is serialized as RFC3339
+                    let mapping = match *part {
+                        "year" => format!("(.{name}|split(\"-\")[0])"),
+                        "month" => format!("(.{name}|split(\"-\")[1])"),
+                        "day" => format!("(.{name}|split(\"-\")[2]|split(\"T\")[0])"),
+                        _ => format!("(.{name}|split(\"T\")[1]|split(\":\")[0])"),
+                    };
+                    out_schema.partition_keys.push(FirehoseType {
+                        name: part,
+                        typ: "string".into(),
+                        comment: comment.clone().into(),
+                        mapping: Some(mapping),
+                    });
+                }
+            },
+            PartitionKey::Field(idx) => {
+                let field = &schema.fields[*idx];
+                let name = field.table_field_name();
+                //Put index into partition key by mapping field as it is, while partition must be string
+                let mapping = if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+                    format!("(if .{name} == null then \"{HIVE_DEFAULT_PARTITION}\" else (.{name}|tostring) end)")
+                } else if matches!(field.typ, FieldType::String | FieldType::Enum) {
+                    format!(".{name}")
+                } else {
+                    format!("(.{name}|tostring)")
+                };
+                out_schema.partition_keys.push(FirehoseType {
+                    name,
+                    typ: field.aws_glue_type(consts),
+                    comment: field.docstring.as_str().into(),
+                    mapping: Some(mapping),
+                });
+            },
+        }
     }
 
     for field in schema.fields.iter() {
-        let name = field.table_field_name();
-        let mut firehose_field = FirehoseType {
-            name,
-            typ: field.typ.aws_glue_type(),
+        //Firehose's date index field should be pushed to the column as it is to be preserved accurately
+        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
+            continue;
+        }
+        out_schema.columns.push(FirehoseType {
+            name: field.table_field_name(),
+            typ: field.aws_glue_type(consts),
             comment: field.docstring.as_str().into(),
             mapping: None,
-        };
+        });
+    }
 
-        //Firehose's date index field should be pushed to the column as it is to be preserved accurately
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
-            //Put index into partition key by mapping field as it is
-            firehose_field.mapping = Some(format!(".{name}"));
-            out_schema.partition_keys.push(firehose_field);
-        } else {
-            out_schema.columns.push(firehose_field)
+    out_schema
+}
+
+///Combines mappings of partition keys into single JQ query for Firehose's `MetadataExtraction`
+pub fn firehose_jq_query(schema: &FirehoseSchema<'_>) -> String {
+    let mut query = String::new();
+    query.push('{');
+    for key in schema.partition_keys.iter() {
+        if query.len() > 1 {
+            query.push(',');
         }
+        let mapping = key.mapping.as_deref().expect("partition key to have mapping");
+        query.push_str(key.name);
+        query.push(':');
+        query.push_str(mapping);
     }
+    query.push('}');
+    query
+}
 
-    serde_json::to_writer_pretty(&mut *out, &out_schema).map_err(|error| io::Error::other(error))?;
-    out.flush()
+///Builds Firehose's S3 prefix using keys extracted by `firehose_jq_query`, matching `shema_firehose_s3_path_prefix`
+pub fn firehose_s3_prefix_template(schema: &FirehoseSchema<'_>, prefix_style: PrefixStyle) -> String {
+    let mut prefix = String::new();
+    for key in schema.partition_keys.iter() {
+        if matches!(prefix_style, PrefixStyle::Hive) {
+            prefix.push_str(key.name);
+            prefix.push('=');
+        }
+        prefix.push_str("!{partitionKeyFromQuery:");
+        prefix.push_str(key.name);
+        prefix.push_str("}/");
+    }
+    prefix
+}
+
+///Writes types of date parts within partition keys' tuple
+fn write_date_parts_type<O: fmt::Write>(parts: &[&str], out: &mut O) -> fmt::Result {
+    for part in parts {
+        match *part {
+            "year" => out.write_str("i32,")?,
+            _ => out.write_str("u8,")?,
+        }
+    }
+    Ok(())
+}
+
+///Writes date parts of `time_field` within partition keys' tuple
+fn write_date_parts<O: fmt::Write>(parts: &[&str], time_field: &str, out: &mut O) -> fmt::Result {
+    for part in parts {
+        match *part {
+            "year" => write!(out, "self.{time_field}.year(),")?,
+            "month" => write!(out, "self.{time_field}.month() as _,")?,
+            part => write!(out, "self.{time_field}.{part}(),")?,
+        }
+    }
+    Ok(())
+}
+
+//Hive metastore's limit on partition value
+const MAX_PARTITION_VALUE_LEN: usize = 256;
+//Hive's partition of null values
+const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+//Writes partition value of reference to field's value
+struct PartitionValue<'a>(&'a Field, &'a str);
+
+impl fmt::Display for PartitionValue<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.1;
+        match self.0.typ {
+            //Enumeration is written by its serialized name, while invalid one is reported by validation instead of failing to write
+            FieldType::Enum => fmt.write_fmt(format_args!("match serde_json::to_value({value}) {{ Ok(serde_json::Value::String(name)) => fmt.write_str(&name)?, Ok(value) => fmt.write_fmt(format_args!(\"{{value}}\"))?, Err(_) => (), }}")),
+            _ => fmt.write_fmt(format_args!("fmt.write_fmt(format_args!(\"{{}}\", {value}))?;")),
+        }
+    }
+}
+
+//Characters escaped by Hive's `FileUtils::escapePathName`, except path separators
+//Firehose writes partition values as they are, hence such values would be read differently by Glue and Athena
+const HIVE_ESCAPED_CHARS: &str = r#"'\u{01}'..='\u{1F}' | '"' | '#' | '%' | '\'' | '*' | ':' | '?' | '\\' | '\u{7F}' | '{' | '[' | ']' | '^'"#;
+
+///Returns name of error type returned by partition validation
+pub fn partition_error_type(schema: &TableSchema) -> String {
+    format!("{}PartitionError", schema.name)
+}
+
+///Generates error type of partition validation, to be declared next to struct
+pub fn generate_firehose_partition_error<O: fmt::Write>(schema: &TableSchema, out: &mut O) -> fmt::Result {
+    let error_type = partition_error_type(schema);
+    writeln!(out, "\n///Invalid partition key of `{}`, specifying its name", schema.name)?;
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]")?;
+    writeln!(out, "pub enum {error_type} {{")?;
+    writeln!(out, "{TAB}///Partition value is empty")?;
+    writeln!(out, "{TAB}Empty(&'static str),")?;
+    writeln!(out, "{TAB}///Enumeration is not serialized as string")?;
+    writeln!(out, "{TAB}NotString(&'static str),")?;
+    writeln!(out, "{TAB}///Partition value is relative path (`.` or `..`)")?;
+    writeln!(out, "{TAB}Relative(&'static str),")?;
+    writeln!(out, "{TAB}///Partition value contains path separator")?;
+    writeln!(out, "{TAB}Separator(&'static str),")?;
+    writeln!(out, "{TAB}///Partition value contains character escaped by Hive")?;
+    writeln!(out, "{TAB}EscapedChar(&'static str),")?;
+    writeln!(out, "{TAB}///Partition value exceeds {MAX_PARTITION_VALUE_LEN} characters")?;
+    writeln!(out, "{TAB}TooLong(&'static str),")?;
+    writeln!(out, "}}\n")?;
+
+    writeln!(out, "impl core::fmt::Display for {error_type} {{")?;
+    writeln!(out, "{TAB}fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{")?;
+    writeln!(out, "{TAB}{TAB}match self {{")?;
+    writeln!(out, "{TAB}{TAB}{TAB}Self::Empty(name) => fmt.write_fmt(format_args!(\"Partition key '{{name}}' is empty\")),")?;
+    writeln!(out, "{TAB}{TAB}{TAB}Self::NotString(name) => fmt.write_fmt(format_args!(\"Partition key '{{name}}' is not serialized as string\")),")?;
+    writeln!(out, "{TAB}{TAB}{TAB}Self::Relative(name) => fmt.write_fmt(format_args!(\"Partition key '{{name}}' is relative path\")),")?;
+    writeln!(out, "{TAB}{TAB}{TAB}Self::Separator(name) => fmt.write_fmt(format_args!(\"Partition key '{{name}}' contains path separator\")),")?;
+    writeln!(out, "{TAB}{TAB}{TAB}Self::EscapedChar(name) => fmt.write_fmt(format_args!(\"Partition key '{{name}}' contains character escaped by Hive\")),")?;
+    writeln!(out, "{TAB}{TAB}{TAB}Self::TooLong(name) => fmt.write_fmt(format_args!(\"Partition key '{{name}}' exceeds {MAX_PARTITION_VALUE_LEN} characters\")),")?;
+    writeln!(out, "{TAB}{TAB}}}")?;
+    writeln!(out, "{TAB}}}")?;
+    writeln!(out, "}}\n")?;
+    writeln!(out, "impl std::error::Error for {error_type} {{}}")
 }
 
 pub fn generate_firehose_partition_accessor<O: fmt::Write>(FirehoseInput { schema, index_time_field }: FirehoseInput<'_>, out: &mut O) -> fmt::Result {
     use fmt::Write;
 
+    let error_type = partition_error_type(schema);
+
+    let date_parts = match index_time_field {
+        Some(_) => schema.date_granularity.parts(),
+        None => &[],
+    };
+
     let mut reference_type = String::new();
     reference_type.push('(');
-    if index_time_field.is_some() {
-        reference_type.push_str("i32,u8,u8,");
-    }
-    for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
-            write!(reference_type, "&'_int {},", field.original_type)?;
+    for key in schema.partition_keys.iter() {
+        match key {
+            PartitionKey::Date => write_date_parts_type(date_parts, &mut reference_type)?,
+            PartitionKey::Field(idx) => write!(reference_type, "&'_int {},", schema.fields[*idx].original_type)?,
         }
     }
     reference_type.push(')');
@@ -117,12 +299,10 @@ pub fn generate_firehose_partition_accessor<O: fmt::Write>(FirehoseInput { schem
     writeln!(out, "{TAB}///Returns tuple with reference to all partition keys")?;
     writeln!(out, "{TAB}pub fn shema_firehose_partition_keys_ref<'_int>(&'_int self) -> {reference_type} {{")?;
     write!(out, "{TAB}{TAB}(")?;
-    if let Some(time_field) = index_time_field {
-        write!(out, "self.{time_field}.year(), self.{time_field}.month() as _, self.{time_field}.day(),", time_field=time_field.original_name)?;
-    }
-    for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
-            write!(out, "&self.{},", field.original_name)?;
+    for key in schema.partition_keys.iter() {
+        match key {
+            PartitionKey::Date => write_date_parts(date_parts, &index_time_field.expect("date partition to have date index").original_name, out)?,
+            PartitionKey::Field(idx) => write!(out, "&self.{},", schema.fields[*idx].original_name)?,
         }
     }
     writeln!(out, ")")?;
@@ -130,74 +310,199 @@ pub fn generate_firehose_partition_accessor<O: fmt::Write>(FirehoseInput { schem
 
     writeln!(out, "{TAB}///Returns owned tuple with reference to all partition keys")?;
     write!(out, "{TAB}pub fn shema_firehose_partition_keys(&self) -> (")?;
-    if index_time_field.is_some() {
-        write!(out, "i32,u8,u8,")?;
-    }
-    for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
-            write!(out, "{},", field.original_type)?;
+    for key in schema.partition_keys.iter() {
+        match key {
+            PartitionKey::Date => write_date_parts_type(date_parts, out)?,
+            PartitionKey::Field(idx) => write!(out, "{},", schema.fields[*idx].original_type)?,
         }
     }
     writeln!(out, ") {{")?;
     write!(out, "{TAB}{TAB}(")?;
-    if let Some(time_field) = index_time_field {
-        write!(out, "self.{time_field}.year(), self.{time_field}.month() as _, self.{time_field}.day(),", time_field=time_field.original_name)?;
-    }
-    for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
-            write!(out, "self.{}.clone(),", field.original_name)?;
+    for key in schema.partition_keys.iter() {
+        match key {
+            PartitionKey::Date => write_date_parts(date_parts, &index_time_field.expect("date partition to have date index").original_name, out)?,
+            PartitionKey::Field(idx) => write!(out, "self.{}.clone(),", schema.fields[*idx].original_name)?,
         }
     }
     writeln!(out, ")")?;
     writeln!(out, "{TAB}}}\n")?;
 
     //Validate partitions are valid
-    writeln!(out, "{TAB}///Returns whether partitions are valid. Specifically it checks if there are no empty partitions")?;
-    writeln!(out, "{TAB}pub fn shema_is_firehose_s3_path_prefix_valid(&self) -> bool {{")?;
-
-    for field in schema.fields.iter() {
-        if field.typ.is_string_type() && field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
-            write!(out, "{TAB}{TAB}if self.{}.trim().is_empty() {{ return false; }}\n", field.original_name)?;
+    writeln!(out, "{TAB}///Validates partition keys, returning error on the first invalid one")?;
+    writeln!(out, "{TAB}pub fn shema_validate_firehose_s3_path_prefix(&self) -> Result<(), {error_type}> {{")?;
+    //Other types are always valid, while missing value goes into default partition
+    let fields = schema.partition_keys.iter().filter_map(|key| match key {
+        PartitionKey::Field(idx) if schema.fields[*idx].typ.is_string_type() || matches!(schema.fields[*idx].typ, FieldType::Enum) => Some(&schema.fields[*idx]),
+        _ => None,
+    }).collect::<Vec<_>>();
+    if !fields.is_empty() {
+        writeln!(out, "{TAB}{TAB}fn shema_validate(name: &'static str, value: &str) -> Result<(), {error_type}> {{")?;
+        writeln!(out, "{TAB}{TAB}{TAB}if value.trim().is_empty() {{ return Err({error_type}::Empty(name)); }}")?;
+        writeln!(out, "{TAB}{TAB}{TAB}if value == \".\" || value == \"..\" {{ return Err({error_type}::Relative(name)); }}")?;
+        writeln!(out, "{TAB}{TAB}{TAB}if value.contains({separators}) {{ return Err({error_type}::Separator(name)); }}", separators=match schema.prefix_style {
+            PrefixStyle::Hive => "['/', '=']",
+            PrefixStyle::Values => "'/'",
+        })?;
+        writeln!(out, "{TAB}{TAB}{TAB}if value.contains(|ch: char| matches!(ch, {HIVE_ESCAPED_CHARS})) {{ return Err({error_type}::EscapedChar(name)); }}")?;
+        writeln!(out, "{TAB}{TAB}{TAB}if value.chars().count() > {MAX_PARTITION_VALUE_LEN} {{ return Err({error_type}::TooLong(name)); }}")?;
+        writeln!(out, "{TAB}{TAB}{TAB}Ok(())")?;
+        writeln!(out, "{TAB}{TAB}}}")?;
+    }
+    for field in fields {
+        let (field_name, name) = (field.original_name.as_str(), field.table_field_name());
+        //Enumeration is validated by its serialized name
+        let validate = match field.typ {
+            FieldType::Enum => format!("match serde_json::to_value(value) {{ Ok(serde_json::Value::String(value)) => shema_validate(\"{name}\", &value)?, _ => return Err({error_type}::NotString(\"{name}\")), }}"),
+            _ => format!("shema_validate(\"{name}\", value)?;"),
+        };
+        if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            writeln!(out, "{TAB}{TAB}if let Some(value) = self.{field_name}.as_ref() {{ {validate} }}")?;
+        } else {
+            writeln!(out, "{TAB}{TAB}{{ let value = &self.{field_name}; {validate} }}")?;
         }
     }
+    writeln!(out, "{TAB}{TAB}Ok(())")?;
+    writeln!(out, "{TAB}}}\n")?;
 
-    write!(out, "{TAB}{TAB}true\n")?;
+    writeln!(out, "{TAB}///Returns whether partitions are valid, i.e. `shema_validate_firehose_s3_path_prefix` succeeds")?;
+    writeln!(out, "{TAB}pub fn shema_is_firehose_s3_path_prefix_valid(&self) -> bool {{")?;
+    writeln!(out, "{TAB}{TAB}self.shema_validate_firehose_s3_path_prefix().is_ok()")?;
     writeln!(out, "{TAB}}}\n")?;
 
     //Firehose compatible s3 path
     writeln!(out, "{TAB}///Returns fmt::Display that can be used to write partitioned path prefix for s3 destination")?;
+    writeln!(out, "{TAB}///")?;
+    writeln!(out, "{TAB}///Partition values are written as they are, the same way as Firehose does, hence they should be checked by `shema_validate_firehose_s3_path_prefix`")?;
     writeln!(out, "{TAB}pub fn shema_firehose_s3_path_prefix(&self) -> impl core::fmt::Display + '_ {{")?;
 
-    writeln!(out, "{TAB}{TAB}pub struct DisplayImpl<'_int>({reference_type});")?;
+    //Marker keeps lifetime in use when there are only date parts
+    writeln!(out, "{TAB}{TAB}pub struct DisplayImpl<'_int>({reference_type}, core::marker::PhantomData<&'_int ()>);")?;
     writeln!(out, "{TAB}{TAB}impl core::fmt::Display for DisplayImpl<'_> {{")?;
     writeln!(out, "{TAB}{TAB}{TAB}fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{")?;
-    write!(out, "{TAB}{TAB}{TAB}{TAB}fmt.write_fmt(format_args!(\"")?;
-    if index_time_field.is_some() {
-        write!(out, "year={{year:04}}/month={{month:02}}/day={{day:02}}/")?;
-    }
-    for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
-            write!(out, "{field_name}={{{field_name}}}/", field_name=field.table_field_name())?;
-        }
-    }
-    write!(out, "\",")?;
-
-    if index_time_field.is_some() {
-        write!(out, "year=self.0.0, month=self.0.1 as u8, day=self.0.2,")?;
-    }
-    let mut idx = 3;
-    for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
-            write!(out, "{}=self.0.{idx},", field.table_field_name())?;
-            idx += 1;
+    let key_prefix = |name: &str| match schema.prefix_style {
+        PrefixStyle::Hive => format!("{name}="),
+        PrefixStyle::Values => String::new(),
+    };
+    let mut idx = 0;
+    for key in schema.partition_keys.iter() {
+        match key {
+            PartitionKey::Date => for part in date_parts {
+                let width = if *part == "year" { 4 } else { 2 };
+                writeln!(out, "{TAB}{TAB}{TAB}{TAB}fmt.write_fmt(format_args!(\"{}{{:0{width}}}/\", self.0.{idx}))?;", key_prefix(part))?;
+                idx += 1;
+            },
+            PartitionKey::Field(field_idx) => {
+                let field = &schema.fields[*field_idx];
+                writeln!(out, "{TAB}{TAB}{TAB}{TAB}fmt.write_str(\"{}\")?;", key_prefix(field.table_field_name()))?;
+                if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+                    writeln!(out, "{TAB}{TAB}{TAB}{TAB}match self.0.{idx} {{")?;
+                    writeln!(out, "{TAB}{TAB}{TAB}{TAB}{TAB}Some(value) => {{ {} }},", PartitionValue(field, "value"))?;
+                    writeln!(out, "{TAB}{TAB}{TAB}{TAB}{TAB}None => fmt.write_str(\"{HIVE_DEFAULT_PARTITION}\")?,")?;
+                    writeln!(out, "{TAB}{TAB}{TAB}{TAB}}}")?;
+                } else {
+                    writeln!(out, "{TAB}{TAB}{TAB}{TAB}{}", PartitionValue(field, &format!("self.0.{idx}")))?;
+                }
+                writeln!(out, "{TAB}{TAB}{TAB}{TAB}fmt.write_str(\"/\")?;")?;
+                idx += 1;
+            },
         }
     }
-    writeln!(out, "))")?;
+    writeln!(out, "{TAB}{TAB}{TAB}{TAB}Ok(())")?;
 
     writeln!(out, "{TAB}{TAB}{TAB}}}\n")?;
     writeln!(out, "{TAB}{TAB}}}\n")?;
-    writeln!(out, "{TAB}{TAB}DisplayImpl(self.shema_firehose_partition_keys_ref())\n")?;
+    writeln!(out, "{TAB}{TAB}DisplayImpl(self.shema_firehose_partition_keys_ref(), core::marker::PhantomData)\n")?;
     writeln!(out, "{TAB}}}\n")?;
 
     Ok(())
 }
+
+//Writes field's value, referred by `value`, as expected by Hive JSON SerDe
+struct FirehoseJsonValue<'a>(&'a Field, &'a str);
+
+impl fmt::Display for FirehoseJsonValue<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.1;
+        if let Some(nested) = self.0.nested.as_ref() {
+            return fmt.write_fmt(format_args!("<{nested}>::shema_firehose_json_object({value}, &mut *out)?;"));
+        }
+        match self.0.typ {
+            //Native collections are declared as glue's array and map
+            FieldType::Array | FieldType::Object if self.0.element.is_some() => fmt.write_fmt(format_args!("serde_json::to_writer(&mut *out, {value})?;")),
+            //Everything else complex is declared as string
+            FieldType::Array | FieldType::Object => fmt.write_fmt(format_args!("serde_json::to_writer(&mut *out, &serde_json::to_string({value})?)?;")),
+            //Enumeration is written by its serialized name, which must be the same as partition key extracted from it, while anything else is declared as string
+            FieldType::Enum => fmt.write_fmt(format_args!("match serde_json::to_value({value})? {{ serde_json::Value::String(name) => serde_json::to_writer(&mut *out, &name)?, value => serde_json::to_writer(&mut *out, &value.to_string())?, }}")),
+            FieldType::TimestampZ => fmt.write_fmt(format_args!(r#"out.write_all(b"\"")?;
+            ({value}).format_into(&mut *out, &time::format_description::well_known::Rfc3339).map_err(std::io::Error::other)?;
+            out.write_all(b"\"")?;"#)),
+            _ => fmt.write_fmt(format_args!("serde_json::to_writer(&mut *out, {value})?;")),
+        }
+    }
+}
+
+pub fn generate_firehose_json_code<O: fmt::Write>(schema: &TableSchema, out: &mut O) -> fmt::Result {
+    writeln!(out, "{TAB}#[doc(hidden)]")?;
+    writeln!(out, "{TAB}///Writes JSON object of record as expected by Firehose's schema")?;
+    writeln!(out, "{TAB}pub fn shema_firehose_json_object<W: std::io::Write>(&self, out: &mut W) -> std::io::Result<()> {{")?;
+    writeln!(out, "{TAB}{TAB}out.write_all(b\"{{\")?;")?;
+    for (idx, field) in schema.fields.iter().enumerate() {
+        let mut key = serde_json::to_string(field.table_field_name()).expect("to serialize field name");
+        key.push(':');
+        if idx > 0 {
+            key.insert(0, ',');
+        }
+        writeln!(out, "\n{TAB}{TAB}out.write_all({key:?}.as_bytes())?;")?;
+        if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            writeln!(out, "{TAB}{TAB}match &self.{} {{", field.original_name)?;
+            writeln!(out, "{TAB}{TAB}{TAB}Some(value) => {{ {} }},", FirehoseJsonValue(field, "value"))?;
+            writeln!(out, "{TAB}{TAB}{TAB}None => out.write_all(b\"null\")?,")?;
+            writeln!(out, "{TAB}{TAB}}}")?;
+        } else {
+            writeln!(out, "{TAB}{TAB}{}", FirehoseJsonValue(field, &format!("&self.{}", field.original_name)))?;
+        }
+    }
+    writeln!(out, "{TAB}{TAB}out.write_all(b\"}}\")")?;
+    writeln!(out, "{TAB}}}\n")?;
+
+    writeln!(out, "{TAB}///Writes record as single line of JSON, matching Firehose's schema")?;
+    writeln!(out, "{TAB}pub fn shema_firehose_json(&self, out: &mut impl std::io::Write) -> std::io::Result<()> {{")?;
+    writeln!(out, "{TAB}{TAB}self.shema_firehose_json_object(&mut *out)?;")?;
+    writeln!(out, "{TAB}{TAB}out.write_all(b\"\\n\")")?;
+    writeln!(out, "{TAB}}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::from_struct;
+
+    #[test]
+    fn should_reject_64_bit_partition_key() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            #[shema(firehose_partition_code)]
+            struct Event {
+                #[shema(index)]
+                shard: u64,
+            }
+        };
+        let payload = match &input.data {
+            syn::Data::Struct(payload) => payload,
+            _ => unreachable!(),
+        };
+        let error = from_struct(&input.attrs, &input.ident, &input.generics, payload).expect_err("to reject u64");
+        assert_eq!(error.to_string(), "Firehose partition key should be string, integer of at most 32 bits, boolean or enumeration but got ULong");
+
+        let input: syn::DeriveInput = syn::parse_quote! {
+            #[shema(firehose_partition_code)]
+            struct Event {
+                #[shema(index)]
+                shard: u32,
+            }
+        };
+        let payload = match &input.data {
+            syn::Data::Struct(payload) => payload,
+            _ => unreachable!(),
+        };
+        from_struct(&input.attrs, &input.ident, &input.generics, payload).expect("to accept u32");
+    }
+}
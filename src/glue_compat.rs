@@ -0,0 +1,119 @@
+//Compatibility rules of glue types.
+//Written as self-contained const fns, as code of this file is also generated to check nested struct's types at compile time.
+
+#[inline]
+const fn is_same(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+    let mut idx = 0;
+    while idx < left.len() {
+        if left[idx] != right[idx] {
+            return false;
+        }
+        idx += 1;
+    }
+    true
+}
+
+//Returns content of `value` enclosed by `prefix` and `>`
+const fn strip_generic<'a>(value: &'a [u8], prefix: &[u8]) -> Option<&'a [u8]> {
+    if value.len() <= prefix.len() || value[value.len() - 1] != b'>' {
+        return None;
+    }
+    let (head, tail) = value.split_at(prefix.len());
+    if !is_same(head, prefix) {
+        return None;
+    }
+    Some(tail.split_at(tail.len() - 1).0)
+}
+
+//Returns length of type or struct's member at start of `value`, which ends with top level `,`
+const fn item_len(value: &[u8]) -> usize {
+    let mut depth = 0usize;
+    let mut idx = 0;
+    while idx < value.len() {
+        match value[idx] {
+            b'<' | b'(' => depth += 1,
+            b'>' | b')' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => return idx,
+            _ => (),
+        }
+        idx += 1;
+    }
+    idx
+}
+
+//Splits `value` into first item and the rest after separating `,`
+const fn split_item(value: &[u8]) -> (&[u8], &[u8]) {
+    let (item, rest) = value.split_at(item_len(value));
+    match rest.split_first() {
+        Some((_, rest)) => (item, rest),
+        None => (item, rest),
+    }
+}
+
+//Splits struct's member into its name and type
+const fn split_member(member: &[u8]) -> (&[u8], &[u8]) {
+    let mut idx = 0;
+    while idx < member.len() {
+        if member[idx] == b':' {
+            let (name, typ) = member.split_at(idx);
+            return (name, typ.split_at(1).1);
+        }
+        idx += 1;
+    }
+    (member, &[])
+}
+
+//Position of integer type in order of its width
+const fn integer_width(typ: &[u8]) -> Option<usize> {
+    const INTEGER_TYPES: [&[u8]; 4] = [b"tinyint", b"smallint", b"int", b"bigint"];
+    let mut idx = 0;
+    while idx < INTEGER_TYPES.len() {
+        if is_same(typ, INTEGER_TYPES[idx]) {
+            return Some(idx);
+        }
+        idx += 1;
+    }
+    None
+}
+
+///Returns whether data written as `old` glue type remains readable as `new` type
+///
+///Primitive types can only be widened, while struct's members are compared recursively and can only be appended
+pub const fn is_compatible_type(old: &[u8], new: &[u8]) -> bool {
+    if is_same(old, new) {
+        return true;
+    }
+    if let (Some(old), Some(new)) = (integer_width(old), integer_width(new)) {
+        return old <= new;
+    }
+    if is_same(old, b"float") && is_same(new, b"double") {
+        return true;
+    }
+    if let (Some(old), Some(new)) = (strip_generic(old, b"array<"), strip_generic(new, b"array<")) {
+        return is_compatible_type(old, new);
+    }
+    if let (Some(old), Some(new)) = (strip_generic(old, b"map<"), strip_generic(new, b"map<")) {
+        let ((old_key, old_value), (new_key, new_value)) = (split_item(old), split_item(new));
+        return is_same(old_key, new_key) && is_compatible_type(old_value, new_value);
+    }
+    if let (Some(mut old), Some(mut new)) = (strip_generic(old, b"struct<"), strip_generic(new, b"struct<")) {
+        while !old.is_empty() {
+            //Member is removed
+            if new.is_empty() {
+                return false;
+            }
+            let ((old_member, old_rest), (new_member, new_rest)) = (split_item(old), split_item(new));
+            let ((old_name, old_type), (new_name, new_type)) = (split_member(old_member), split_member(new_member));
+            if !is_same(old_name, new_name) || !is_compatible_type(old_type, new_type) {
+                return false;
+            }
+            old = old_rest;
+            new = new_rest;
+        }
+        return true;
+    }
+    false
+}
@@ -0,0 +1,183 @@
+use super::{TableSchema, Field, FieldType, FieldFlag};
+use super::utils::{self, ConstExpr};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(serde_derive::Serialize)]
+#[serde(untagged)]
+enum JsonType {
+    Single(&'static str),
+    Nullable([&'static str; 2]),
+}
+
+#[derive(serde_derive::Serialize)]
+#[serde(untagged)]
+enum JsonSchema<'a> {
+    //Placeholder of nested struct's schema
+    Const(String),
+    Property(Box<JsonProperty<'a>>),
+}
+
+#[derive(serde_derive::Serialize, Default)]
+struct JsonProperty<'a> {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    typ: Option<JsonType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    minimum: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maximum: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    items: Option<JsonSchema<'a>>,
+    #[serde(rename = "additionalProperties", skip_serializing_if = "Option::is_none")]
+    additional_properties: Option<JsonSchema<'a>>,
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    enumeration: Option<Vec<Option<&'a str>>>,
+    #[serde(rename = "anyOf", skip_serializing_if = "Option::is_none")]
+    any_of: Option<Vec<JsonSchema<'a>>>,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    description: &'a str,
+}
+
+//Serializes properties as object in order of declaration
+struct JsonProperties<'a>(Vec<(&'a str, JsonSchema<'a>)>);
+
+impl serde::Serialize for JsonProperties<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, schema) in self.0.iter() {
+            map.serialize_entry(name, schema)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(serde_derive::Serialize)]
+struct JsonObject<'a> {
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    schema: Option<&'static str>,
+    title: String,
+    #[serde(rename = "type")]
+    typ: &'static str,
+    properties: JsonProperties<'a>,
+    required: Vec<&'a str>,
+}
+
+impl FieldType {
+    #[inline]
+    fn json_schema_property(&self) -> JsonProperty<'static> {
+        let (typ, minimum, maximum) = match self {
+            Self::Byte => ("integer", Some(i8::MIN as i64), Some(i8::MAX as u64)),
+            Self::Short => ("integer", Some(i16::MIN as i64), Some(i16::MAX as u64)),
+            Self::Integer => ("integer", Some(i32::MIN as i64), Some(i32::MAX as u64)),
+            Self::Long => ("integer", Some(i64::MIN), Some(i64::MAX as u64)),
+            Self::UByte => ("integer", Some(0), Some(u8::MAX as u64)),
+            Self::UShort => ("integer", Some(0), Some(u16::MAX as u64)),
+            Self::UInteger => ("integer", Some(0), Some(u32::MAX as u64)),
+            Self::ULong | Self::Decimal => ("integer", Some(0), Some(u64::MAX)),
+            Self::Float | Self::Double => ("number", None, None),
+            Self::String => ("string", None, None),
+            Self::Boolean => ("boolean", None, None),
+            Self::TimestampZ => return JsonProperty {
+                typ: Some(JsonType::Single("string")),
+                format: Some("date-time"),
+                ..Default::default()
+            },
+            Self::Array => ("array", None, None),
+            //json fields and enumerations can be anything, depending on serializer
+            Self::Object | Self::Enum => return JsonProperty::default(),
+            Self::Struct => unreachable!("nested struct's schema is declared by its own derive"),
+        };
+        JsonProperty {
+            typ: Some(JsonType::Single(typ)),
+            minimum,
+            maximum,
+            ..Default::default()
+        }
+    }
+}
+
+impl Field {
+    fn json_schema<'a>(&'a self, consts: &mut Vec<ConstExpr>) -> JsonSchema<'a> {
+        let is_optional = self.typ_flags.is_type_flag(FieldFlag::Optional);
+
+        if let Some(nested) = self.nested.as_ref() {
+            let schema = JsonSchema::Const(utils::const_placeholder(consts, format!("<{nested}>::SHEMA_JSON_SCHEMA_OBJECT"), 0));
+            if !is_optional && self.docstring.is_empty() {
+                return schema;
+            }
+
+            let mut any_of = vec![schema];
+            if is_optional {
+                any_of.push(JsonSchema::Property(Box::new(JsonProperty {
+                    typ: Some(JsonType::Single("null")),
+                    ..Default::default()
+                })));
+            }
+            return JsonSchema::Property(Box::new(JsonProperty {
+                any_of: Some(any_of),
+                description: self.docstring.as_str(),
+                ..Default::default()
+            }));
+        }
+
+        let mut property = match (self.element, self.symbols.as_ref()) {
+            (Some(element), _) if matches!(self.typ, FieldType::Object) => JsonProperty {
+                typ: Some(JsonType::Single("object")),
+                additional_properties: Some(JsonSchema::Property(Box::new(element.json_schema_property()))),
+                ..Default::default()
+            },
+            (Some(element), _) => JsonProperty {
+                typ: Some(JsonType::Single("array")),
+                items: Some(JsonSchema::Property(Box::new(element.json_schema_property()))),
+                ..Default::default()
+            },
+            (None, Some(symbols)) => {
+                let mut enumeration = symbols.values.iter().map(|symbol| Some(symbol.as_str())).collect::<Vec<_>>();
+                if is_optional {
+                    enumeration.push(None);
+                }
+                JsonProperty {
+                    enumeration: Some(enumeration),
+                    ..Default::default()
+                }
+            },
+            (None, None) => self.typ.json_schema_property(),
+        };
+        if is_optional {
+            if let Some(JsonType::Single(typ)) = property.typ {
+                property.typ = Some(JsonType::Nullable([typ, "null"]));
+            }
+        }
+        property.description = self.docstring.as_str();
+
+        JsonSchema::Property(Box::new(property))
+    }
+}
+
+///Generates json schema of struct's object, using placeholders for nested objects
+///
+///Top level schema additionally declares `$schema`
+pub fn generate_json_schema(schema: &TableSchema, is_top_level: bool, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    let mut object = JsonObject {
+        schema: if is_top_level { Some(DRAFT) } else { None },
+        title: schema.lower_cased_table_name(),
+        typ: "object",
+        properties: JsonProperties(Vec::with_capacity(schema.fields.len())),
+        required: Vec::new(),
+    };
+
+    for field in schema.fields.iter() {
+        let name = field.table_field_name();
+        object.properties.0.push((name, field.json_schema(consts)));
+        if !field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            object.required.push(name);
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&object).expect("to serialize json schema");
+    utils::write_json_unquoting_consts(&json, out, consts);
+}
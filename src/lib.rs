@@ -4,18 +4,123 @@
 //!
 //!## Struct parameters
 //!
-//! - `firehose_schema` - Enables firehose schema generation
-//! - `firehose_partition_code` - Enables code generation to access partition information
+//! - `firehose_schema` - Enables firehose schema generation. Together with `firehose_partition_code` it also declares `SHEMA_FIREHOSE_JQ_QUERY` and `SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE` to configure delivery stream's dynamic partitioning consistently with `shema_firehose_s3_path_prefix`
+//! - `firehose_partition_code` - Enables code generation to access partition information. Partition values are written into S3 prefix as they are, the same way as Firehose does, hence `shema_validate_firehose_s3_path_prefix` returns `<Struct>PartitionError` naming partition key that is enumeration not serialized as string, is empty, is relative path (`.` or `..`), contains path separator or character escaped by Hive (e.g. `:` or `%`), or exceeds 256 characters (Hive metastore's limit). Only valid values are guaranteed to be written into the same prefix locally and by Firehose
 //! - `firehose_parquet_schema` - Enables parquet schema generation similar to AWS Glue's one
+//! - `firehose_json` - Specifies to generate code to write struct as single line of JSON in the shape expected by `firehose_schema` (timestamps as RFC3339 strings, fields encoded as json strings and enumerations by their serialized names or as json strings if variant is not serialized as string). Structs of `nested` fields must enable it too. This requires `serde_json` and `time` (with `formatting` feature) crates to be added as dependencies
 //! - `parquet_code` - Specifies to generate parquet code to write struct per schema. This requires `parquet` and `serde_json` crates to be added as dependencies
+//! - `parquet_reader_code` - Specifies to generate parquet code to read struct per schema. This requires `parquet`, `serde_json` and `time` crates to be added as dependencies. Struct must own all its fields and partition keys are initialized with `Default::default()` as they are not stored by Firehose
+//! - `parquet_sink` - Specifies to generate `<Struct>ParquetSink` that writes structs into local directory partitioned by `shema_firehose_s3_path_prefix`, as Firehose delivers them to S3, finalizing parquet files by number of rows or size. Records with invalid partition (see `shema_validate_firehose_s3_path_prefix`) are rejected. Requires `parquet_code` and `firehose_partition_code`
+//! - `timestamp` - Specifies parquet encoding of timestamps. One of `int96` (default, as Firehose's Hive serializer), `millis`, `micros` or `nanos` (`INT64` with `TIMESTAMP(unit, true)` logical type). Argument MUST be string specified as `timestamp = "micros"`
+//! - `parquet_nested` - Specifies to encode all eligible collections as native parquet `LIST` and `MAP` (see field's `native`) unless field is marked as `json`
+//! - `athena_ddl` - Enables generation of Athena's `CREATE EXTERNAL TABLE` statement for parquet data written by firehose
+//! - `athena_location` - Specifies `LOCATION` of Athena's table. Argument MUST be string specified as `athena_location = "s3://bucket/prefix/"`
+//! - `athena_projection` - Enables partition projection with range of years for date keys of `firehose_date_index`. Other partition keys are projected as `injected`, hence must be specified in query. Argument MUST be string specified as `athena_projection = "2020,2100"`
+//! - `athena_property` - Adds property to `TBLPROPERTIES`. Can be specified multiple times. Argument MUST be string specified as `athena_property = "parquet.compression=SNAPPY"`
+//! - `partition_order` - Specifies order of partition keys within glue schema, partition accessors and S3 prefix as comma separated list of `index` fields' names and `date` (date parts of `firehose_date_index`). Defaults to date parts followed by `index` fields in order of declaration. Argument MUST be string specified as `partition_order = "client_id,date"`
+//! - `s3_prefix_style` - Specifies layout of S3 prefix: `hive` (default) as `key=value/` or `values` as `value/` (Athena's projection then declares `storage.location.template` based on `athena_location`). Argument MUST be string specified as `s3_prefix_style = "values"`
+//! - `schema_baseline` - Specifies path (relative to crate's root) of committed `SHEMA_BASELINE` to fail compilation if schema is incompatible with it. Requires `firehose_schema`. Argument MUST be string specified as `schema_baseline = "schemas/event.json"`
+//! - `export_dir` - Specifies directory (relative to crate's root) to write every enabled output into when struct is compiled, as `<SHEMA_TABLE_NAME>.<extension>` (e.g. `firehose.json`, `parquet.txt`, `athena.sql`, `glue_table.tf`, `avsc`). Files with the same content are left untouched. Outputs that depend on `nested` struct are rejected, as nested types are only known when generated code is compiled (use `shema dump` of [command line tool](#command-line-tool) for them instead). Argument MUST be string specified as `export_dir = "schemas"`
+//! - `postgres` - Enables generation of PostgreSQL's `CREATE TABLE` statement. Fields marked as `index` are declared via `CREATE INDEX` and field's documentation via `COMMENT ON COLUMN`
+//! - `sqlite` - Enables generation of SQLite's `CREATE TABLE` statement. Fields marked as `index` are declared via `CREATE INDEX`
+//! - `sqlite_code` - Specifies to generate [rusqlite](https://crates.io/crates/rusqlite) code to insert struct. This requires `rusqlite`, `serde_json` and `time` (with `formatting` feature) crates to be added as dependencies
+//! - `clickhouse` - Enables generation of ClickHouse's `CREATE TABLE` statement with `MergeTree` engine, partitioned by `firehose_date_index` (by day at most) and ordered by `index` fields
+//! - `avro` - Enables generation of Apache Avro's record schema
+//! - `json_schema` - Enables generation of JSON schema (draft 2020-12) to validate struct's serialized form
+//! - `arrow_code` - Specifies to generate code to build [arrow](https://crates.io/crates/arrow) schema and convert slice of structs into `RecordBatch`, using the same types as `parquet_code`. Arrow cannot write `int96` timestamps, hence `timestamp` MUST be specified as `millis`, `micros` or `nanos` for timestamp fields. This requires `arrow-schema`, `arrow-array` and `serde_json` crates to be added as dependencies
+//! - `bigquery_schema` - Enables generation of BigQuery's table schema (JSON accepted by `bq mk`). Additionally declares `timePartitioning` by `firehose_date_index` with its granularity and `clustering` by `index` fields (at most 4), if struct has such fields
+//! - `terraform` - Enables generation of Terraform's `partition_keys` and `storage_descriptor` blocks of `aws_glue_catalog_table` (using `athena_location` as location) and, if struct has partition keys (`index` or `firehose_date_index` fields), `processing_configuration` block of `aws_kinesis_firehose_delivery_stream` with JQ query to extract partition keys
+//! - `runtime` - Specifies to implement `Shema` trait of [shema-runtime](https://crates.io/crates/shema-runtime) crate, describing table name and fields to generic code. This requires `shema-runtime` crate to be added as dependency
 //!
 //!## Field parameters
 //!
 //!- `json` - Specifies that field is to be encoded as json object (automatically derived for std's collections)
 //!- `enumeration` - Specifies that field is to be encoded as enumeration (Depending on database, it will be encoded as string or object)
-//!- `index` - Specifies that field is to be indexed by underlying database engine (e.g. to be declared a partition key in AWS glue schema)
-//!- `firehose_date_index` - Specifies field to be used as timestamp within `firehose` schema which will produce `year`, `month` and `day` fields. Requires to be of `timestamp` type. E.g. [time::OffsetDateTime](https://docs.rs/time/0.3.44/time/struct.OffsetDateTime.html)
+//!- `index` - Specifies that field is to be indexed by underlying database engine (e.g. to be declared a partition key in AWS glue schema). Firehose partition key must be string, integer of at most 32 bits (JQ cannot represent 64-bit integers precisely), boolean or enumeration (written by its serialized name, which must be string and requires `serde_json` crate). Missing value of optional partition key is written as `__HIVE_DEFAULT_PARTITION__`
+//!- `firehose_date_index` - Specifies field to be used as timestamp within `firehose` schema which will produce `year`, `month` and `day` fields. Granularity can be specified as `firehose_date_index = "hour"` to produce date parts up to `year`, `month`, `day` (default) or `hour`. Requires to be of `timestamp` type. E.g. [time::OffsetDateTime](https://docs.rs/time/0.3.44/time/struct.OffsetDateTime.html)
 //!- `rename` - Tells to use different name for the field. Argument MUST be string specified as `rename = "new_name"`
+//!- `decimal` - Specifies that `u64` field is to be encoded as `DECIMAL(20,0)` instead of `bigint`, which otherwise fails to write values that overflow `i64`
+//!- `timestamp` - Overrides struct's `timestamp` encoding for the field
+//!- `native` - Specifies that `Vec`, `HashSet` or `HashMap<String, _>` of primitive type is to be encoded as native parquet `LIST` or `MAP` instead of json string. Glue type is declared as `array<T>` or `map<string,T>`
+//!- `nested` - Specifies that field's type (or `Option` of it) is another struct deriving `Shema`, to be encoded as glue `struct<...>` and parquet group of its columns. Nested struct must enable the same outputs as its parent, as its types are only generated for enabled outputs, and its partition keys are skipped
+//!- `symbols` - Specifies serialized names of `enumeration` variants, allowing to declare it as Avro's `enum`. Argument MUST be string specified as `symbols = "Open,Closed"`
+//!
+//!### Unsigned integers
+//!
+//!Glue has no unsigned types, hence unsigned integers are widened to the next signed type (`u8` as `smallint`, `u16` as `int`, `u32` as `bigint`).
+//!
+//!`u64` is declared as `bigint` and write fails if value cannot fit `i64`, unless field is marked as `decimal`.
+//!
+//!Parquet output uses unsigned integer logical types.
+//!
+//!### PostgreSQL
+//!
+//!Unsigned integers are widened the same way as for Glue and `decimal` is declared as `numeric(20,0)`.
+//!
+//!Fields encoded as json, enumerations and `nested` structs are declared as `jsonb`, while `native` arrays are declared as postgres arrays.
+//!
+//!### SQLite
+//!
+//!Timestamps are stored as RFC3339 strings and `decimal` as decimal strings, while fields encoded as json, enumerations, collections and `nested` structs are stored as json strings.
+//!
+//!### ClickHouse
+//!
+//!Optional fields are declared as `Nullable` except for `native` collections which cannot be null, hence `None` is to be inserted as empty collection.
+//!
+//!Enumerations are declared as `LowCardinality(String)`, while fields encoded as json and `nested` structs are declared as `String`.
+//!
+//!### Avro
+//!
+//!Optional fields are declared as union with `null` defaulting to `null`, timestamps use `timestamp-micros` logical type and `decimal` is declared as `bytes` with `decimal` logical type.
+//!
+//!Fields encoded as json and enumerations without `symbols` are declared as `string`. Record and enum types are named after Rust type and defined by first field using them, while any other field refers to them by name.
+//!
+//!### JSON schema
+//!
+//!Non-optional fields are required, integers are limited by range of their type and timestamps are expected as `date-time` formatted strings.
+//!
+//!Fields encoded as json and enumerations without `symbols` accept any value, as their form depends on serializer.
+//!
+//!### Schema evolution
+//!
+//!Baseline of `schema_baseline` is compared with current schema when struct is compiled, failing on the following changes:
+//!
+//!- Table name or partition keys (names, types and order) are changed
+//!- Column is removed or columns are reordered
+//!- Column's type is changed, except widening of integers (e.g. `int` to `bigint`) and `float` to `double`
+//!- Column is changed from required to optional within parquet
+//!
+//!Columns appended after existing ones are allowed. Members of `nested` struct are compared by the same rules recursively, once its type is known when generated code is compiled.
+//!
+//!### Exported files
+//!
+//!Outputs of `export_dir` are named after constants they contain:
+//!
+//!- `firehose.json` - `SHEMA_FIREHOSE_SCHEMA`
+//!- `baseline.json` - `SHEMA_BASELINE`
+//!- `firehose.jq` - `SHEMA_FIREHOSE_JQ_QUERY`
+//!- `s3_prefix.txt` - `SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE`
+//!- `parquet.txt` - `SHEMA_FIREHOSE_PARQUET_SCHEMA`
+//!- `athena.sql` - `SHEMA_ATHENA_DDL`
+//!- `glue_table.tf` - `SHEMA_TERRAFORM_GLUE_TABLE`
+//!- `firehose_processing.tf` - `SHEMA_TERRAFORM_FIREHOSE_PROCESSING`
+//!- `postgres.sql` - `SHEMA_POSTGRES_DDL`
+//!- `sqlite.sql` - `SHEMA_SQLITE_DDL`
+//!- `sqlite_insert.sql` - `SHEMA_SQLITE_INSERT`
+//!- `clickhouse.sql` - `SHEMA_CLICKHOUSE_DDL`
+//!- `avsc` - `SHEMA_AVRO_SCHEMA`
+//!- `schema.json` - `SHEMA_JSON_SCHEMA`
+//!- `bigquery.json` - `SHEMA_BIGQUERY_SCHEMA`
+//!- `bigquery_time_partitioning.json` - `SHEMA_BIGQUERY_TIME_PARTITIONING`
+//!- `bigquery_clustering.json` - `SHEMA_BIGQUERY_CLUSTERING`
+//!
+//!Files are written during macro expansion, which is not tracked by incremental compilation:
+//!
+//!- Files are only written when crate is recompiled, hence deleted or edited files are not restored until struct's crate is rebuilt
+//!- Any expansion writes them, including `cargo check` and IDE's analysis, while concurrent builds may write the same files
+//!- Files are not removed when output, struct or `export_dir` itself is removed
+//!
+//!Prefer `shema dump` of [command line tool](#command-line-tool) to produce files as explicit build step, e.g. in CI.
 //!
 //!### Firehose date index
 //!
@@ -31,18 +136,44 @@
 //!
 //!- `SHEMA_TABLE_NAME` - table name in lower case
 //!- `SHEMA_FIREHOSE_SCHEMA` - Firehose glue table schema. If enabled.
+//!- `SHEMA_BASELINE` - Glue partition keys and columns with parquet repetition of columns in JSON, to be committed as `schema_baseline`. If `firehose_schema` is enabled.
+//!- `SHEMA_FIREHOSE_JQ_QUERY` - JQ query of Firehose's `MetadataExtraction` to extract partition keys. If `firehose_schema` or `firehose_partition_code` is enabled.
+//!- `SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE` - Firehose's S3 prefix using keys of `SHEMA_FIREHOSE_JQ_QUERY`. If `firehose_schema` or `firehose_partition_code` is enabled.
 //!- `SHEMA_FIREHOSE_PARQUET_SCHEMA` - Partquet schema compatible with firehose data stream. If enabled.
+//!- `SHEMA_ATHENA_DDL` - Athena's `CREATE EXTERNAL TABLE` statement with partition keys declared in `PARTITIONED BY`. If enabled.
+//!- `SHEMA_POSTGRES_DDL` - PostgreSQL's `CREATE TABLE` statement followed by indexes and comments. If enabled.
+//!- `SHEMA_SQLITE_DDL` - SQLite's `CREATE TABLE` statement followed by indexes. If enabled.
+//!- `SHEMA_SQLITE_INSERT` - SQLite's `INSERT` statement with parameter per field in order of declaration. If `sqlite_code` is enabled.
+//!- `SHEMA_CLICKHOUSE_DDL` - ClickHouse's `CREATE TABLE` statement. If enabled.
+//!- `SHEMA_AVRO_SCHEMA` - Avro's record schema in JSON. If enabled.
+//!- `SHEMA_JSON_SCHEMA` - JSON schema of struct. If enabled.
+//!- `SHEMA_BIGQUERY_SCHEMA` - BigQuery's table schema in JSON. If `bigquery_schema` is enabled.
+//!- `SHEMA_BIGQUERY_TIME_PARTITIONING` - BigQuery's `timePartitioning` in JSON. If `bigquery_schema` is enabled and struct has `firehose_date_index`.
+//!- `SHEMA_BIGQUERY_CLUSTERING` - BigQuery's `clustering` in JSON. If `bigquery_schema` is enabled and struct has `index` fields.
+//!- `SHEMA_TERRAFORM_GLUE_TABLE` - Terraform's body of `aws_glue_catalog_table` with `partition_keys` and `storage_descriptor` blocks. If `terraform` is enabled.
+//!- `SHEMA_TERRAFORM_FIREHOSE_PROCESSING` - Terraform's `processing_configuration` block of `aws_kinesis_firehose_delivery_stream`. If `terraform` is enabled and struct has `index` or `firehose_date_index` fields.
 //!
 //!### Following methods will be defined for affected structs
 //!
 //!- `shema_firehose_partition_keys_ref` - Returns tuple with references to partition keys
 //!- `shema_firehose_partition_keys` - Returns tuple with owned values of partition keys
 //!- `shema_firehose_s3_path_prefix` - Returns `fmt::Display` type that writes full path prefix for S3 destination object
-//!- `shema_is_firehose_s3_path_prefix_valid` - Returns `true` if `shema_firehose_s3_path_prefix` is valid or not (i.e. no string is empty among partitions)
+//!- `shema_firehose_json` - Writes struct as single line of JSON to be sent to Firehose
+//!- `shema_validate_firehose_s3_path_prefix` - Returns `<Struct>PartitionError` naming the first partition key that is enumeration not serialized as string, is empty, is relative path, contains path separator or character escaped by Hive, or exceeds 256 characters
+//!- `shema_is_firehose_s3_path_prefix_valid` - Returns `true` if `shema_firehose_s3_path_prefix` is valid or not (i.e. `shema_validate_firehose_s3_path_prefix` succeeds)
+//!- `shema_sqlite_bind` - Binds all fields as parameters of `SHEMA_SQLITE_INSERT` statement
+//!- `shema_sqlite_insert` - Inserts struct using `SHEMA_SQLITE_INSERT` statement cached by connection
+//!- `shema_arrow_schema` - Returns arrow `Schema` of struct
+//!- `shema_arrow_record_batch` - Converts slice of structs into arrow `RecordBatch` with `shema_arrow_schema`
 //!
 //!### Following [parquet](https://crates.io/crates/parquet)  crate traits are implemented:
 //!
 //!- [RecordWriter](https://docs.rs/parquet/57.0.0/parquet/record/trait.RecordWriter.html) - Enables write via [SerializedFileWriter](https://docs.rs/parquet/latest/parquet/file/writer/struct.SerializedFileWriter.html)
+//!- [RecordReader](https://docs.rs/parquet/57.0.0/parquet/record/trait.RecordReader.html) - Enables read via [SerializedFileReader](https://docs.rs/parquet/latest/parquet/file/serialized_reader/struct.SerializedFileReader.html). Implemented for `Vec<T>`
+//!
+//!### Following [shema-runtime](https://crates.io/crates/shema-runtime) crate traits are implemented:
+//!
+//!- `Shema` - Provides `TABLE_NAME` and `FIELDS` with name, Rust name, kind, flags and documentation of each field. If `runtime` is enabled.
 //!
 //!### Firehose specifics
 //!
@@ -92,15 +223,40 @@
 //!
 //!assert_eq!(Analytics::SHEMA_TABLE_NAME, "analytics");
 //!```
+//!
+//!## Command line tool
+//!
+//!`shema` binary of `shema-cli` (install via `cargo install --path cli`) parses sources with the same code as derive, printing outputs of structs without compiling crate:
+//!
+//!- `shema dump [--struct <NAME>] [--format <FORMAT>] [PATH...]` - Prints outputs of structs within Rust files or directories (`src` by default)
+//!- `shema diff [--struct <NAME>] [--format <FORMAT>] <FROM> <TO> [PATH...]` - Prints difference between outputs at git revisions, exiting with code 1 if there is any
+//!
+//!`FORMAT` is name of constant, with or without `SHEMA_` prefix (e.g. `firehose_schema` or `athena_ddl`). Files of `schema_baseline` and `export_dir` are neither checked nor written. Structs are identified (and `nested` types resolved) by their names, hence struct deriving `Shema` with the same name as another one within `PATH` is reported as error.
 
 #![allow(clippy::style)]
+//Unit tests are not built as proc-macro, hence lack derive's entry point
+#![cfg_attr(all(test, not(proc_macro)), allow(dead_code))]
 
 mod utils;
 mod firehose;
 mod parquet;
+mod athena;
+mod postgres;
+mod sqlite;
+mod clickhouse;
+mod avro;
+mod json_schema;
+mod arrow;
+mod bigquery;
+mod terraform;
+mod runtime;
+mod baseline;
+mod glue_compat;
+mod export;
 
 use core::fmt::{self, Write};
 
+#[cfg(proc_macro)]
 use proc_macro::TokenStream;
 
 const ATTR_NAME: &str = "shema";
@@ -112,6 +268,12 @@ enum FieldType {
     Short,
     Integer,
     Long,
+    UByte,
+    UShort,
+    UInteger,
+    ULong,
+    //u64 encoded as DECIMAL(20,0) to fit whole range of values
+    Decimal,
     Float,
     Double,
     String,
@@ -120,6 +282,8 @@ enum FieldType {
     Array,
     Object,
     Enum,
+    //Nested struct deriving Shema
+    Struct,
 }
 
 impl FieldType {
@@ -127,6 +291,59 @@ impl FieldType {
     pub const fn is_string_type(&self) -> bool {
         matches!(self, Self::String)
     }
+
+    #[inline]
+    ///Returns whether type can be written as partition value
+    pub const fn is_partition_type(&self) -> bool {
+        //JQ parses numbers as doubles, hence 64-bit integers would lose precision within partition key
+        matches!(self, Self::Byte | Self::Short | Self::Integer | Self::UByte | Self::UShort | Self::UInteger | Self::String | Self::Boolean | Self::Enum)
+    }
+
+    #[inline]
+    pub const fn is_primitive_type(&self) -> bool {
+        matches!(self, Self::Byte | Self::Short | Self::Integer | Self::Long | Self::UByte | Self::UShort | Self::UInteger | Self::ULong | Self::Float | Self::Double | Self::String | Self::Boolean)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum TimestampEncoding {
+    //Deprecated encoding used by Firehose's Hive serializer
+    Int96,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum DateGranularity {
+    Year,
+    Month,
+    Day,
+    Hour,
+}
+
+impl DateGranularity {
+    #[inline(always)]
+    ///Returns names of date parts up to granularity, used as partition keys
+    fn parts(&self) -> &'static [&'static str] {
+        &["year", "month", "day", "hour"][..*self as usize + 1]
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum PartitionKey {
+    //Date parts of firehose's date index
+    Date,
+    //Index of partition key within fields
+    Field(usize),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum PrefixStyle {
+    //`key=value/`
+    Hive,
+    //`value/`
+    Values,
 }
 
 #[repr(u8)]
@@ -159,6 +376,14 @@ struct Field {
     original_name: String,
     original_type: String,
     typ_flags: FieldFlagContainer,
+    //Parquet encoding of timestamp, only relevant for FieldType::TimestampZ
+    timestamp: TimestampEncoding,
+    //Element type of collection to be encoded as native parquet LIST (Array) or MAP with string keys (Object)
+    element: Option<FieldType>,
+    //Type of nested struct, only relevant for FieldType::Struct
+    nested: Option<String>,
+    //Variants of enumeration, if specified
+    symbols: Option<EnumSymbols>,
     //Optional documentation on field
     docstring: String,
 }
@@ -171,17 +396,45 @@ impl Field {
     }
 }
 
+struct EnumSymbols {
+    //Name of enumeration's type
+    name: String,
+    //Serialized names of variants
+    values: Vec<String>,
+}
+
 struct Outputs {
     firehose_schema: bool,
     firehose_parquet_schema: bool,
     firehose_partition_code: bool,
     parquet_code: bool,
+    parquet_reader_code: bool,
+    parquet_nested: bool,
+    athena_ddl: bool,
+    postgres: bool,
+    sqlite: bool,
+    sqlite_code: bool,
+    clickhouse: bool,
+    avro: bool,
+    json_schema: bool,
+    arrow_code: bool,
+    bigquery_schema: bool,
+    terraform: bool,
+    firehose_json: bool,
+    parquet_sink: bool,
+    runtime: bool,
 }
 
 struct TableSchema {
     name: String,
     fields: Vec<Field>,
     outputs: Outputs,
+    athena: athena::AthenaOptions,
+    //Granularity of partitions derived from firehose's date index
+    date_granularity: DateGranularity,
+    //Partition keys in order of S3 prefix
+    partition_keys: Vec<PartitionKey>,
+    prefix_style: PrefixStyle,
 }
 
 impl TableSchema {
@@ -198,11 +451,58 @@ impl TableSchema {
 
 #[cold]
 #[inline(never)]
-fn compile_error(input: &impl quote::ToTokens, error: impl fmt::Display) -> TokenStream {
-    syn::Error::new_spanned(input, error).to_compile_error().into()
+fn compile_error(input: &impl quote::ToTokens, error: impl fmt::Display) -> syn::Error {
+    syn::Error::new_spanned(input, error)
+}
+
+fn extract_str_literal<'a>(value: &'a syn::MetaNameValue, name: &str) -> Result<&'a syn::LitStr, syn::Error> {
+    match &value.value {
+        syn::Expr::Lit(literal) => match &literal.lit {
+            syn::Lit::Str(literal) => Ok(literal),
+            _ => Err(compile_error(&value.value, format_args!("'{name}' should be literal string"))),
+        }
+        _ => Err(compile_error(&value.value, format_args!("'{name}' should be literal string"))),
+    }
+}
+
+///Resolves path relative to root of crate being compiled
+fn manifest_relative_path(literal: &syn::LitStr) -> std::path::PathBuf {
+    match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(root) => std::path::Path::new(&root).join(literal.value()),
+        None => std::path::PathBuf::from(literal.value()),
+    }
+}
+
+fn extract_timestamp_encoding(value: &syn::MetaNameValue) -> Result<TimestampEncoding, syn::Error> {
+    let literal = match &value.value {
+        syn::Expr::Lit(literal) => match &literal.lit {
+            syn::Lit::Str(literal) => literal,
+            _ => return Err(compile_error(&value.value, "'timestamp' should be literal string")),
+        }
+        _ => return Err(compile_error(&value.value, "'timestamp' should be literal string")),
+    };
+
+    match literal.value().as_str() {
+        "int96" => Ok(TimestampEncoding::Int96),
+        "millis" => Ok(TimestampEncoding::Millis),
+        "micros" => Ok(TimestampEncoding::Micros),
+        "nanos" => Ok(TimestampEncoding::Nanos),
+        _ => Err(compile_error(literal, "'timestamp' should be one of: int96, millis, micros, nanos")),
+    }
+}
+
+fn extract_date_granularity(value: &syn::MetaNameValue) -> Result<DateGranularity, syn::Error> {
+    let literal = extract_str_literal(value, "firehose_date_index")?;
+    match literal.value().as_str() {
+        "year" => Ok(DateGranularity::Year),
+        "month" => Ok(DateGranularity::Month),
+        "day" => Ok(DateGranularity::Day),
+        "hour" => Ok(DateGranularity::Hour),
+        _ => Err(compile_error(literal, "'firehose_date_index' should be one of: year, month, day, hour")),
+    }
 }
 
-fn extract_type_path_segment(segment: &syn::PathSegment) -> Result<FieldType, TokenStream> {
+fn extract_type_path_segment(segment: &syn::PathSegment) -> Result<FieldType, syn::Error> {
     if segment.ident == "bool" {
         Ok(FieldType::Boolean)
     } else if segment.ident == "i8" {
@@ -213,6 +513,14 @@ fn extract_type_path_segment(segment: &syn::PathSegment) -> Result<FieldType, To
         Ok(FieldType::Integer)
     } else if segment.ident == "i64" || segment.ident == "isize" {
         Ok(FieldType::Long)
+    } else if segment.ident == "u8" {
+        Ok(FieldType::UByte)
+    } else if segment.ident == "u16" {
+        Ok(FieldType::UShort)
+    } else if segment.ident == "u32" {
+        Ok(FieldType::UInteger)
+    } else if segment.ident == "u64" || segment.ident == "usize" {
+        Ok(FieldType::ULong)
     } else if segment.ident == "f32" {
         Ok(FieldType::Float)
     } else if segment.ident == "f64" {
@@ -221,21 +529,17 @@ fn extract_type_path_segment(segment: &syn::PathSegment) -> Result<FieldType, To
         Ok(FieldType::TimestampZ)
     } else if segment.ident == "String" || segment.ident == "str" {
         Ok(FieldType::String)
-    } else if segment.ident == "Vec" || segment.ident == "HashSet" || segment.ident == " BTreeSet" {
+    } else if segment.ident == "Vec" || segment.ident == "HashSet" || segment.ident == "BTreeSet" {
         Ok(FieldType::Array)
-    } else if segment.ident == "HashMap" || segment.ident == " BTreeMap" {
+    } else if segment.ident == "HashMap" || segment.ident == "BTreeMap" {
         Ok(FieldType::Object)
-    } else if segment.ident == "u8" || segment.ident == "u16" ||
-              segment.ident == "u32" || segment.ident == "u64" ||
-              segment.ident == "usize" {
-        Err(compile_error(segment, "Unsigned integers are not supported"))
     } else {
         Err(compile_error(segment, format_args!("Unrecognized type: {}", segment.ident)))
     }
 }
 
 //Returns flag indicating optionality and field type on success.
-fn extract_type_path(ty: &syn::TypePath, type_override: Option<FieldType>) -> Result<(bool, FieldType), TokenStream> {
+fn extract_type_path(ty: &syn::TypePath, type_override: Option<FieldType>) -> Result<(bool, FieldType), syn::Error> {
     let ty = ty.path.segments.last().expect("to have at least one segment");
 
     if let Some(type_override) = type_override {
@@ -262,7 +566,89 @@ fn extract_type_path(ty: &syn::TypePath, type_override: Option<FieldType>) -> Re
     }
 }
 
-fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn::Generics, payload: &syn::DataStruct) -> TokenStream {
+//Returns element type of collection if it can be encoded as native parquet LIST or MAP
+fn extract_native_element(ty: &syn::Type) -> Option<FieldType> {
+    fn type_argument(segment: &syn::PathSegment, idx: usize) -> Option<&syn::PathSegment> {
+        match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.iter().filter(|arg| matches!(arg, syn::GenericArgument::Type(_))).nth(idx) {
+                Some(syn::GenericArgument::Type(syn::Type::Path(ty))) => ty.path.segments.last(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    let ty = match ty {
+        syn::Type::Path(ty) => ty,
+        syn::Type::Reference(ty) => match &*ty.elem {
+            syn::Type::Path(ty) => ty,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let mut segment = ty.path.segments.last()?;
+    if segment.ident == "Option" {
+        segment = type_argument(segment, 0)?;
+    }
+
+    let element = if segment.ident == "Vec" || segment.ident == "HashSet" {
+        type_argument(segment, 0)?
+    } else if segment.ident == "HashMap" {
+        if !matches!(extract_type_path_segment(type_argument(segment, 0)?), Ok(FieldType::String)) {
+            return None;
+        }
+        type_argument(segment, 1)?
+    } else {
+        return None;
+    };
+
+    match extract_type_path_segment(element) {
+        Ok(element) if element.is_primitive_type() => Some(element),
+        _ => None,
+    }
+}
+
+//Returns type of nested struct, unwrapping Option if necessary
+fn extract_nested_type(ty: &syn::Type) -> Option<String> {
+    let ty = match ty {
+        syn::Type::Path(ty) => ty,
+        _ => return None,
+    };
+    let segment = ty.path.segments.last()?;
+    if segment.ident == "Option" {
+        match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(syn::GenericArgument::Type(ty @ syn::Type::Path(_))) => Some(quote::quote!(#ty).to_string()),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        Some(quote::quote!(#ty).to_string())
+    }
+}
+
+//Returns name of type, unwrapping Option if necessary
+fn extract_type_name(ty: &syn::Type) -> Option<String> {
+    let ty = match ty {
+        syn::Type::Path(ty) => ty,
+        _ => return None,
+    };
+    let segment = ty.path.segments.last()?;
+    if segment.ident == "Option" {
+        match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(syn::GenericArgument::Type(ty)) => extract_type_name(ty),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        Some(segment.ident.to_string())
+    }
+}
+
+pub(crate) fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn::Generics, payload: &syn::DataStruct) -> Result<String, syn::Error> {
     let mut schema = TableSchema {
         name: ident.to_string(),
         fields: Vec::new(),
@@ -271,8 +657,31 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
             firehose_parquet_schema: false,
             firehose_partition_code: false,
             parquet_code: false,
-        }
+            parquet_reader_code: false,
+            parquet_nested: false,
+            athena_ddl: false,
+            postgres: false,
+            sqlite: false,
+            sqlite_code: false,
+            clickhouse: false,
+            avro: false,
+            json_schema: false,
+            arrow_code: false,
+            bigquery_schema: false,
+            terraform: false,
+            firehose_json: false,
+            parquet_sink: false,
+            runtime: false,
+        },
+        athena: Default::default(),
+        date_granularity: DateGranularity::Day,
+        partition_keys: Vec::new(),
+        prefix_style: PrefixStyle::Hive,
     };
+    let mut partition_order = None;
+    let mut schema_baseline = None;
+    let mut export_dir = None;
+    let mut timestamp_encoding = TimestampEncoding::Int96;
 
     for attr in attributes.iter() {
         match &attr.meta {
@@ -283,7 +692,7 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
                     );
                     let nested = match nested {
                         Ok(nested) => nested,
-                        Err(error) => return compile_error(&value, format_args!("shema input is not valid: {error}")),
+                        Err(error) => return Err(compile_error(&value, format_args!("shema input is not valid: {error}"))),
                     };
 
                     for meta in nested {
@@ -298,11 +707,99 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
                                     schema.outputs.firehose_partition_code = true;
                                 } else if value.is_ident("parquet_code") {
                                     schema.outputs.parquet_code = true;
+                                } else if value.is_ident("parquet_reader_code") {
+                                    schema.outputs.parquet_reader_code = true;
+                                } else if value.is_ident("parquet_nested") {
+                                    schema.outputs.parquet_nested = true;
+                                } else if value.is_ident("athena_ddl") {
+                                    schema.outputs.athena_ddl = true;
+                                } else if value.is_ident("postgres") {
+                                    schema.outputs.postgres = true;
+                                } else if value.is_ident("sqlite") {
+                                    schema.outputs.sqlite = true;
+                                } else if value.is_ident("sqlite_code") {
+                                    schema.outputs.sqlite_code = true;
+                                } else if value.is_ident("clickhouse") {
+                                    schema.outputs.clickhouse = true;
+                                } else if value.is_ident("avro") {
+                                    schema.outputs.avro = true;
+                                } else if value.is_ident("json_schema") {
+                                    schema.outputs.json_schema = true;
+                                } else if value.is_ident("arrow_code") {
+                                    schema.outputs.arrow_code = true;
+                                } else if value.is_ident("bigquery_schema") {
+                                    schema.outputs.bigquery_schema = true;
+                                } else if value.is_ident("terraform") {
+                                    schema.outputs.terraform = true;
+                                } else if value.is_ident("firehose_json") {
+                                    schema.outputs.firehose_json = true;
+                                } else if value.is_ident("parquet_sink") {
+                                    schema.outputs.parquet_sink = true;
+                                } else if value.is_ident("runtime") {
+                                    schema.outputs.runtime = true;
                                 } else {
-                                    return compile_error(meta_path, "Unknown attribute passed to shema");
+                                    return Err(compile_error(meta_path, "Unknown attribute passed to shema"));
                                 }
                             }
-                            invalid => return compile_error(invalid, format_args!("Unknown attribute passed to shema: {:?}", invalid)),
+                            syn::Meta::NameValue(value) => if value.path.is_ident("timestamp") {
+                                timestamp_encoding = match extract_timestamp_encoding(value) {
+                                    Ok(timestamp_encoding) => timestamp_encoding,
+                                    Err(error) => return Err(error),
+                                };
+                            } else if value.path.is_ident("athena_location") {
+                                match extract_str_literal(value, "athena_location") {
+                                    Ok(literal) => schema.athena.location = Some(literal.value()),
+                                    Err(error) => return Err(error),
+                                }
+                            } else if value.path.is_ident("athena_projection") {
+                                let literal = match extract_str_literal(value, "athena_projection") {
+                                    Ok(literal) => literal,
+                                    Err(error) => return Err(error),
+                                };
+                                let literal_value = literal.value();
+                                let range = literal_value.split_once(',').and_then(|(start, end)| Some((start.trim().parse::<u16>().ok()?, end.trim().parse::<u16>().ok()?)));
+                                schema.athena.projection = match range {
+                                    Some((start, end)) if start <= end => Some((start, end)),
+                                    _ => return Err(compile_error(literal, "'athena_projection' should be range of years specified as \"start,end\"")),
+                                };
+                            } else if value.path.is_ident("partition_order") {
+                                match extract_str_literal(value, "partition_order") {
+                                    Ok(literal) => partition_order = Some(literal.clone()),
+                                    Err(error) => return Err(error),
+                                }
+                            } else if value.path.is_ident("schema_baseline") {
+                                match extract_str_literal(value, "schema_baseline") {
+                                    Ok(literal) => schema_baseline = Some(literal.clone()),
+                                    Err(error) => return Err(error),
+                                }
+                            } else if value.path.is_ident("export_dir") {
+                                match extract_str_literal(value, "export_dir") {
+                                    Ok(literal) => export_dir = Some(literal.clone()),
+                                    Err(error) => return Err(error),
+                                }
+                            } else if value.path.is_ident("s3_prefix_style") {
+                                let literal = match extract_str_literal(value, "s3_prefix_style") {
+                                    Ok(literal) => literal,
+                                    Err(error) => return Err(error),
+                                };
+                                schema.prefix_style = match literal.value().as_str() {
+                                    "hive" => PrefixStyle::Hive,
+                                    "values" => PrefixStyle::Values,
+                                    _ => return Err(compile_error(literal, "'s3_prefix_style' should be one of: hive, values")),
+                                };
+                            } else if value.path.is_ident("athena_property") {
+                                let literal = match extract_str_literal(value, "athena_property") {
+                                    Ok(literal) => literal,
+                                    Err(error) => return Err(error),
+                                };
+                                match literal.value().split_once('=') {
+                                    Some((key, value)) if !key.trim().is_empty() => schema.athena.properties.push((key.trim().to_owned(), value.trim().to_owned())),
+                                    _ => return Err(compile_error(literal, "'athena_property' should be specified as \"key=value\"")),
+                                }
+                            } else {
+                                return Err(compile_error(meta_path, "Unknown attribute passed to shema"));
+                            },
+                            invalid => return Err(compile_error(invalid, format_args!("Unknown attribute passed to shema: {:?}", invalid))),
                         }
                     }
                 }
@@ -311,23 +808,32 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
         }
     }
 
+    if schema.outputs.parquet_sink && !(schema.outputs.parquet_code && schema.outputs.firehose_partition_code) {
+        return Err(compile_error(ident, "'parquet_sink' requires 'parquet_code' and 'firehose_partition_code'"));
+    }
+
     for field in payload.fields.iter() {
         let original_name = match field.ident.as_ref() {
             Some(ident) => ident.to_string(),
-            None => return compile_error(field, "Field is missing name"),
+            None => return Err(compile_error(field, "Field is missing name")),
         };
 
         let mut field_name = None;
         let mut docstring = String::new();
         let mut typ_flags = FieldFlagContainer(0);
         let mut type_override = None;
+        let mut is_decimal = false;
+        let mut timestamp = timestamp_encoding;
+        let mut is_native = false;
+        let mut symbols = None;
+        let mut date_granularity = None;
 
         for attr in field.attrs.iter() {
             match &attr.meta {
                 syn::Meta::NameValue(value) if value.path.is_ident("doc") => {
                     let literal = match &value.value {
                         syn::Expr::Lit(literal) => &literal.lit,
-                        _ => return compile_error(&value.value, "Docstring should be literal string"),
+                        _ => return Err(compile_error(&value.value, "Docstring should be literal string")),
                     };
                     if let syn::Lit::Str(text) = literal {
                         docstring.push_str(&text.value());
@@ -338,7 +844,7 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
                     let nested = attr.parse_args_with(syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated);
                     let nested = match nested {
                         Ok(nested) => nested,
-                        Err(error) => return compile_error(value, format_args!("'{ATTR_NAME}' attribute input is not valid: {error}")),
+                        Err(error) => return Err(compile_error(value, format_args!("'{ATTR_NAME}' attribute input is not valid: {error}"))),
                     };
 
                     for meta in nested {
@@ -352,28 +858,58 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
                                 typ_flags.set_type_flag(FieldFlag::Index);
                             } else if value.is_ident("firehose_date_index") {
                                 typ_flags.set_type_flag(FieldFlag::FirehoseDateIndex);
+                                date_granularity = Some(DateGranularity::Day);
+                            } else if value.is_ident("decimal") {
+                                is_decimal = true;
+                            } else if value.is_ident("native") {
+                                is_native = true;
+                            } else if value.is_ident("nested") {
+                                type_override = Some(FieldType::Struct);
                             } else {
-                                return compile_error(meta_path, "Unexpected path attribute specified for '{ATTR_NAME}'. Allowed: json, enumeration, index");
+                                return Err(compile_error(meta_path, format_args!("Unexpected path attribute specified for '{ATTR_NAME}'. Allowed: json, enumeration, index, firehose_date_index, decimal, native, nested")));
                             },
                             syn::Meta::NameValue(value) => if value.path.is_ident("rename") {
                                 let literal = match &value.value {
                                     syn::Expr::Lit(literal) => match &literal.lit {
                                         syn::Lit::Str(literal) => literal,
-                                        _ => return compile_error(&value.value, "'rename' should be literal string"),
+                                        _ => return Err(compile_error(&value.value, "'rename' should be literal string")),
                                     }
-                                    _ => return compile_error(&value.value, "'rename' should be literal string"),
+                                    _ => return Err(compile_error(&value.value, "'rename' should be literal string")),
                                 };
                                 let new_name = literal.value();
                                 let new_name = new_name.trim();
                                 if new_name.is_empty() {
-                                    return compile_error(literal, "'rename' requires non-empty string");
+                                    return Err(compile_error(literal, "'rename' requires non-empty string"));
                                 }
 
                                 field_name = Some(new_name.to_owned());
+                            } else if value.path.is_ident("timestamp") {
+                                timestamp = match extract_timestamp_encoding(value) {
+                                    Ok(timestamp) => timestamp,
+                                    Err(error) => return Err(error),
+                                };
+                            } else if value.path.is_ident("firehose_date_index") {
+                                typ_flags.set_type_flag(FieldFlag::FirehoseDateIndex);
+                                date_granularity = match extract_date_granularity(value) {
+                                    Ok(date_granularity) => Some(date_granularity),
+                                    Err(error) => return Err(error),
+                                };
+                            } else if value.path.is_ident("symbols") {
+                                let literal = match extract_str_literal(value, "symbols") {
+                                    Ok(literal) => literal,
+                                    Err(error) => return Err(error),
+                                };
+                                let values = literal.value().split(',').map(|symbol| symbol.trim().to_owned()).collect::<Vec<_>>();
+                                //Avro's naming rules are the most strict
+                                let is_valid = |symbol: &String| symbol.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_') && symbol.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+                                if !values.iter().all(is_valid) {
+                                    return Err(compile_error(literal, "'symbols' should be comma separated list of names consisting of alphanumeric characters and '_'"));
+                                }
+                                symbols = Some(values);
                             } else {
-                                return compile_error(meta_path, "Unexpected name value attribute specified for '{ATTR_NAME}'. Allowed: rename");
+                                return Err(compile_error(meta_path, format_args!("Unexpected name value attribute specified for '{ATTR_NAME}'. Allowed: rename, timestamp, firehose_date_index, symbols")));
                             },
-                            unexpected => return compile_error(unexpected, format_args!("Unexpected value provided to '{ATTR_NAME}': {unexpected:?}"))
+                            unexpected => return Err(compile_error(unexpected, format_args!("Unexpected value provided to '{ATTR_NAME}': {unexpected:?}")))
                         }
                     }
                 },
@@ -384,28 +920,80 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
         let field_ty = &field.ty;
         let original_type = quote::quote!(#field_ty).to_string();
 
-        let (is_optional, typ) = match field_ty {
+        let (is_optional, mut typ) = match field_ty {
             syn::Type::Path(ty) => match extract_type_path(ty, type_override) {
                 Ok(result) => result,
-                Err(error) => return error,
+                Err(error) => return Err(error),
             },
             syn::Type::Reference(ty) => match &*ty.elem {
                 syn::Type::Path(ty) => match extract_type_path(ty, type_override) {
                     Ok(result) => result,
-                    Err(error) => return error,
+                    Err(error) => return Err(error),
                 },
-                unexpected => return compile_error(unexpected, "Field type should be type path"),
+                unexpected => return Err(compile_error(unexpected, "Field type should be type path")),
             }
-            unexpected => return compile_error(unexpected, "Field type should be type path"),
+            unexpected => return Err(compile_error(unexpected, "Field type should be type path")),
         };
 
         docstring.pop();
+        if is_decimal {
+            if matches!(typ, FieldType::ULong) {
+                typ = FieldType::Decimal;
+            } else {
+                return Err(compile_error(&field.ty, format_args!("'decimal' is only supported for u64 but got {:?}", typ)));
+            }
+        }
         if is_optional {
             typ_flags.set_type_flag(FieldFlag::Optional);
         }
+        let mut element = None;
+        if is_native || (schema.outputs.parquet_nested && type_override.is_none()) {
+            element = match typ {
+                FieldType::Array | FieldType::Object => extract_native_element(&field.ty),
+                _ => None,
+            };
+            if is_native && element.is_none() {
+                return Err(compile_error(&field.ty, "'native' requires Vec, HashSet or HashMap<String, _> of primitive type"));
+            }
+        }
+        let mut nested = None;
+        if matches!(typ, FieldType::Struct) {
+            if typ_flags.is_type_flag(FieldFlag::Index) {
+                return Err(compile_error(&field.ty, "'nested' field cannot be used as index"));
+            }
+            nested = match extract_nested_type(&field.ty) {
+                Some(nested) => Some(nested),
+                None => return Err(compile_error(&field.ty, "'nested' requires struct type path")),
+            };
+        }
         if typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) && !matches!(typ, FieldType::TimestampZ) {
-            return compile_error(&field.ty, format_args!("Firehose date index should be timestamp but got {:?}", typ));
+            return Err(compile_error(&field.ty, format_args!("Firehose date index should be timestamp but got {:?}", typ)));
+        }
+        //Arrow's parquet writer has no INT96 counterpart of timestamp
+        if schema.outputs.arrow_code && matches!(typ, FieldType::TimestampZ) && matches!(timestamp, TimestampEncoding::Int96) {
+            return Err(compile_error(field, format_args!("'arrow_code' cannot write int96 timestamp of '{original_name}'. Specify 'timestamp' as one of: millis, micros, nanos")));
+        }
+        let is_firehose = schema.outputs.firehose_schema || schema.outputs.firehose_partition_code || schema.outputs.athena_ddl || schema.outputs.terraform;
+        if is_firehose && typ_flags.is_type_flag(FieldFlag::Index) && !typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) && !typ.is_partition_type() {
+            return Err(compile_error(&field.ty, format_args!("Firehose partition key should be string, integer of at most 32 bits, boolean or enumeration but got {:?}", typ)));
+        }
+        if let Some(date_granularity) = date_granularity {
+            if schema.index_time_field().is_some() {
+                return Err(compile_error(field, "Only one field can be specified as 'firehose_date_index'"));
+            }
+            schema.date_granularity = date_granularity;
         }
+        let symbols = match symbols {
+            Some(values) if matches!(typ, FieldType::Enum) => match extract_type_name(&field.ty) {
+                Some(name) => Some(EnumSymbols {
+                    name,
+                    values,
+                }),
+                None => return Err(compile_error(&field.ty, "'symbols' requires enumeration type path")),
+            },
+            Some(_) => return Err(compile_error(&field.ty, "'symbols' can only be specified for 'enumeration'")),
+            None => None,
+        };
 
         schema.fields.push(Field {
             name: field_name.unwrap_or_else(|| original_name.clone()),
@@ -413,19 +1001,122 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
             original_name,
             original_type,
             typ_flags,
+            timestamp,
+            element,
+            nested,
+            symbols,
             docstring
         })
     }
 
+    //Date parts go first unless specified otherwise
+    if schema.index_time_field().is_some() {
+        schema.partition_keys.push(PartitionKey::Date);
+    }
+    for (idx, field) in schema.fields.iter().enumerate() {
+        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
+            schema.partition_keys.push(PartitionKey::Field(idx));
+        }
+    }
+    if let Some(literal) = partition_order {
+        let mut partition_keys = Vec::with_capacity(schema.partition_keys.len());
+        for name in literal.value().split(',').map(str::trim) {
+            let key = schema.partition_keys.iter().find(|key| match key {
+                PartitionKey::Date => name == "date",
+                PartitionKey::Field(idx) => name == schema.fields[*idx].table_field_name(),
+            });
+            match key {
+                Some(key) if !partition_keys.contains(key) => partition_keys.push(*key),
+                Some(_) => return Err(compile_error(&literal, format_args!("'partition_order' specifies '{name}' more than once"))),
+                None => return Err(compile_error(&literal, format_args!("'partition_order' specifies unknown partition key '{name}'. Expected 'date' or name of 'index' field"))),
+            }
+        }
+        if partition_keys.len() != schema.partition_keys.len() {
+            return Err(compile_error(&literal, "'partition_order' should specify all partition keys"));
+        }
+        schema.partition_keys = partition_keys;
+    }
+
+    let mut baseline_path = None;
+    if let Some(literal) = schema_baseline {
+        if !schema.outputs.firehose_schema {
+            return Err(compile_error(&literal, "'schema_baseline' requires 'firehose_schema'"));
+        }
+        let path = manifest_relative_path(&literal);
+        let input = firehose::FirehoseInput {
+            index_time_field: schema.index_time_field(),
+            schema: &schema,
+        };
+        //Files of crate are only relevant when it is compiled, rather than inspected by CLI
+        let mut nested_check = String::new();
+        if cfg!(proc_macro) {
+            if let Err(error) = baseline::check_baseline(input, &path, &mut nested_check) {
+                return Err(compile_error(&literal, error));
+            }
+        }
+        baseline_path = Some((path, nested_check));
+    }
+
     let (impl_gen, type_gen, where_clause) = generics.split_for_impl();
     let mut code = String::new();
 
     let table_name = schema.lower_cased_table_name();
+    //Outputs to be written into `export_dir`
+    let mut exports = export::Exports::new();
     //impl start
     let _ = writeln!(code, "impl{} {}{} {{", quote::quote!(#impl_gen), ident, quote::quote!(#type_gen #where_clause));
     let _ = writeln!(code, "{TAB}pub const SHEMA_TABLE_NAME: &'static str = \"{table_name}\";");
 
-    if schema.outputs.firehose_schema || schema.outputs.firehose_partition_code {
+    //Nested struct's types to be used by parent struct with the same outputs
+    if schema.outputs.firehose_schema || schema.outputs.athena_ddl || schema.outputs.terraform {
+        let mut consts = Vec::new();
+        let mut glue_type = String::new();
+        firehose::generate_glue_struct_type(&schema, &mut glue_type, &mut consts);
+        let _ = write!(code, "{TAB}#[doc(hidden)]\n{TAB}pub const SHEMA_GLUE_TYPE: &'static str = ");
+        utils::write_const_str(&mut code, &glue_type, &consts);
+        let _ = writeln!(code, ";");
+    }
+
+    if schema.outputs.firehose_parquet_schema {
+        let mut consts = Vec::new();
+        let mut parquet_fields = String::new();
+        parquet::generate_parquet_fields(&schema, &mut parquet_fields, &mut consts);
+        parquet_fields.pop();
+        let _ = write!(code, "{TAB}#[doc(hidden)]\n{TAB}pub const SHEMA_PARQUET_FIELDS: &'static str = ");
+        utils::write_const_str(&mut code, &parquet_fields, &consts);
+        let _ = writeln!(code, ";");
+    }
+
+    if schema.outputs.avro {
+        let mut consts = Vec::new();
+        let mut avro_record = String::new();
+        avro::generate_avro_record(&schema, &mut avro_record, &mut consts);
+        let _ = write!(code, "{TAB}#[doc(hidden)]\n{TAB}pub const SHEMA_AVRO_RECORD: &'static str = ");
+        utils::write_const_str(&mut code, &avro_record, &consts);
+        let _ = writeln!(code, ";");
+        exports.add("avsc", &avro_record, &consts);
+    }
+
+    if schema.outputs.json_schema {
+        let mut consts = Vec::new();
+        let mut json_schema = String::new();
+        json_schema::generate_json_schema(&schema, false, &mut json_schema, &mut consts);
+        let _ = write!(code, "{TAB}#[doc(hidden)]\n{TAB}pub const SHEMA_JSON_SCHEMA_OBJECT: &'static str = ");
+        utils::write_const_str(&mut code, &json_schema, &consts);
+        let _ = writeln!(code, ";");
+    }
+
+    if schema.outputs.bigquery_schema {
+        let mut consts = Vec::new();
+        let mut bigquery_fields = String::new();
+        bigquery::generate_bigquery_fields(&schema, &mut bigquery_fields, &mut consts);
+        let _ = write!(code, "{TAB}#[doc(hidden)]\n{TAB}pub const SHEMA_BIGQUERY_FIELDS: &'static str = ");
+        utils::write_const_str(&mut code, &bigquery_fields, &consts);
+        let _ = writeln!(code, ";");
+        exports.add("bigquery.json", &bigquery_fields, &consts);
+    }
+
+    if schema.outputs.firehose_schema || schema.outputs.firehose_partition_code || schema.outputs.athena_ddl || schema.outputs.terraform {
         let schema = firehose::FirehoseInput {
             index_time_field: schema.index_time_field(),
             schema: &schema,
@@ -433,12 +1124,69 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
 
         if schema.schema.outputs.firehose_schema {
             //Firehose schema
-            let _ = write!(code, "{TAB}pub const SHEMA_FIREHOSE_SCHEMA: &'static str = r#\"");
+            let mut consts = Vec::new();
+            let mut out = Vec::new();
+            firehose::generate_firehose_schema(schema, &mut out, &mut consts).expect("to generate firehose schema");
+            let _ = write!(code, "{TAB}pub const SHEMA_FIREHOSE_SCHEMA: &'static str = ");
             //json generates valid string always
-            unsafe {
-                firehose::generate_firehose_schema(schema, &mut code.as_mut_vec()).expect("to generate firehose schema");
+            let out = String::from_utf8(out).expect("valid utf-8");
+            utils::write_const_str(&mut code, &out, &consts);
+            let _ = writeln!(code, ";");
+            exports.add("firehose.json", &out, &consts);
+
+            let mut consts = Vec::new();
+            let mut out = String::new();
+            baseline::generate_baseline(schema, &mut out, &mut consts);
+            let _ = write!(code, "{TAB}pub const SHEMA_BASELINE: &'static str = ");
+            utils::write_const_str(&mut code, &out, &consts);
+            let _ = writeln!(code, ";");
+            exports.add("baseline.json", &out, &consts);
+        }
+
+        if schema.schema.outputs.firehose_schema || schema.schema.outputs.firehose_partition_code {
+            //Firehose's dynamic partitioning configuration
+            let firehose_schema = firehose::firehose_schema(schema, &mut Vec::new());
+            let query = firehose::firehose_jq_query(&firehose_schema);
+            let _ = write!(code, "{TAB}pub const SHEMA_FIREHOSE_JQ_QUERY: &'static str = ");
+            utils::write_const_str(&mut code, &query, &[]);
+            let _ = writeln!(code, ";");
+            exports.add("firehose.jq", &query, &[]);
+            let prefix = firehose::firehose_s3_prefix_template(&firehose_schema, schema.schema.prefix_style);
+            let _ = write!(code, "{TAB}pub const SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE: &'static str = ");
+            utils::write_const_str(&mut code, &prefix, &[]);
+            let _ = writeln!(code, ";");
+            exports.add("s3_prefix.txt", &prefix, &[]);
+        }
+
+        if schema.schema.outputs.athena_ddl {
+            //Athena's table DDL
+            let mut consts = Vec::new();
+            let mut out = String::new();
+            athena::generate_athena_ddl(schema, &mut out, &mut consts);
+            let _ = write!(code, "{TAB}pub const SHEMA_ATHENA_DDL: &'static str = ");
+            utils::write_const_str(&mut code, &out, &consts);
+            let _ = writeln!(code, ";");
+            exports.add("athena.sql", &out, &consts);
+        }
+
+        if schema.schema.outputs.terraform {
+            //Terraform blocks of glue table
+            let mut consts = Vec::new();
+            let mut out = String::new();
+            terraform::generate_glue_table(schema, &mut out, &mut consts);
+            let _ = write!(code, "{TAB}pub const SHEMA_TERRAFORM_GLUE_TABLE: &'static str = ");
+            utils::write_const_str(&mut code, &out, &consts);
+            let _ = writeln!(code, ";");
+            exports.add("glue_table.tf", &out, &consts);
+
+            if !schema.schema.partition_keys.is_empty() {
+                let mut out = String::new();
+                terraform::generate_firehose_processing_configuration(schema, &mut out);
+                let _ = write!(code, "{TAB}pub const SHEMA_TERRAFORM_FIREHOSE_PROCESSING: &'static str = ");
+                utils::write_const_str(&mut code, &out, &[]);
+                let _ = writeln!(code, ";");
+                exports.add("firehose_processing.tf", &out, &[]);
             }
-            let _ = writeln!(code, "\"#;");
         }
 
         if schema.schema.outputs.firehose_partition_code {
@@ -449,15 +1197,146 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
 
     if schema.outputs.firehose_parquet_schema {
         //Firehose's parquet schema
-        let _ = write!(code, "{TAB}pub const SHEMA_FIREHOSE_PARQUET_SCHEMA: &'static str = r#\"");
-        unsafe {
-            parquet::generate_parquet_schema(&schema, &mut code.as_mut_vec()).expect("to generate parquet schema");
+        let mut consts = Vec::new();
+        let mut out = String::new();
+        parquet::generate_parquet_schema(&schema, &mut out, &mut consts);
+        let _ = write!(code, "{TAB}pub const SHEMA_FIREHOSE_PARQUET_SCHEMA: &'static str = ");
+        utils::write_const_str(&mut code, &out, &consts);
+        let _ = writeln!(code, ";");
+        exports.add("parquet.txt", &out, &consts);
+    }
+
+    if schema.outputs.postgres {
+        //PostgreSQL's table DDL
+        let mut out = String::new();
+        postgres::generate_postgres_ddl(&schema, &mut out);
+        let _ = write!(code, "{TAB}pub const SHEMA_POSTGRES_DDL: &'static str = ");
+        utils::write_const_str(&mut code, &out, &[]);
+        let _ = writeln!(code, ";");
+        exports.add("postgres.sql", &out, &[]);
+    }
+
+    if schema.outputs.sqlite {
+        //SQLite's table DDL
+        let mut out = String::new();
+        sqlite::generate_sqlite_ddl(&schema, &mut out);
+        let _ = write!(code, "{TAB}pub const SHEMA_SQLITE_DDL: &'static str = ");
+        utils::write_const_str(&mut code, &out, &[]);
+        let _ = writeln!(code, ";");
+        exports.add("sqlite.sql", &out, &[]);
+    }
+
+    if schema.outputs.sqlite_code {
+        let mut out = String::new();
+        sqlite::generate_sqlite_insert(&schema, &mut out);
+        let _ = write!(code, "{TAB}pub const SHEMA_SQLITE_INSERT: &'static str = ");
+        utils::write_const_str(&mut code, &out, &[]);
+        let _ = writeln!(code, ";");
+        exports.add("sqlite_insert.sql", &out, &[]);
+        sqlite::generate_sqlite_code(&schema, &mut code).expect("to generate sqlite code");
+    }
+
+    if schema.outputs.clickhouse {
+        //ClickHouse's table DDL
+        let mut out = String::new();
+        clickhouse::generate_clickhouse_ddl(&schema, &mut out);
+        let _ = write!(code, "{TAB}pub const SHEMA_CLICKHOUSE_DDL: &'static str = ");
+        utils::write_const_str(&mut code, &out, &[]);
+        let _ = writeln!(code, ";");
+        exports.add("clickhouse.sql", &out, &[]);
+    }
+
+    if schema.outputs.avro {
+        //Avro schema is the same as struct's record
+        let _ = writeln!(code, "{TAB}pub const SHEMA_AVRO_SCHEMA: &'static str = Self::SHEMA_AVRO_RECORD;");
+    }
+
+    if schema.outputs.json_schema {
+        //JSON schema of struct
+        let mut consts = Vec::new();
+        let mut out = String::new();
+        json_schema::generate_json_schema(&schema, true, &mut out, &mut consts);
+        let _ = write!(code, "{TAB}pub const SHEMA_JSON_SCHEMA: &'static str = ");
+        utils::write_const_str(&mut code, &out, &consts);
+        let _ = writeln!(code, ";");
+        exports.add("schema.json", &out, &consts);
+    }
+
+    if schema.outputs.bigquery_schema {
+        //BigQuery schema is the same as struct's fields
+        let _ = writeln!(code, "{TAB}pub const SHEMA_BIGQUERY_SCHEMA: &'static str = Self::SHEMA_BIGQUERY_FIELDS;");
+
+        if let Some(partitioning) = bigquery::generate_bigquery_time_partitioning(&schema) {
+            let _ = write!(code, "{TAB}pub const SHEMA_BIGQUERY_TIME_PARTITIONING: &'static str = ");
+            utils::write_const_str(&mut code, &partitioning, &[]);
+            let _ = writeln!(code, ";");
+            exports.add("bigquery_time_partitioning.json", &partitioning, &[]);
+        }
+
+        let keys = schema.fields.iter().filter(|field| field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex)).collect::<Vec<_>>();
+        if keys.len() > bigquery::MAX_CLUSTERING_FIELDS {
+            return Err(compile_error(ident, format_args!("BigQuery allows at most {} clustering fields but got {} 'index' fields", bigquery::MAX_CLUSTERING_FIELDS, keys.len())));
+        }
+        if let Some(field) = keys.iter().find(|field| !field.is_bigquery_clustering_type()) {
+            return Err(compile_error(ident, format_args!("'index' field '{}' cannot be used for BigQuery clustering", field.original_name)));
+        }
+        if let Some(clustering) = bigquery::generate_bigquery_clustering(&keys) {
+            let _ = write!(code, "{TAB}pub const SHEMA_BIGQUERY_CLUSTERING: &'static str = ");
+            utils::write_const_str(&mut code, &clustering, &[]);
+            let _ = writeln!(code, ";");
+            exports.add("bigquery_clustering.json", &clustering, &[]);
         }
-        let _ = writeln!(code, "\"#;");
+    }
+
+    let self_type = format!("{}{}", ident, quote::quote!(#type_gen));
+    if schema.outputs.parquet_code {
+        parquet::generate_parquet_writer_columns_code(&schema, &mut code).expect("to generate parquet code");
+    }
+    if schema.outputs.parquet_reader_code {
+        parquet::generate_parquet_reader_columns_code(&schema, &mut code).expect("to generate parquet code");
+    }
+    if schema.outputs.firehose_json {
+        firehose::generate_firehose_json_code(&schema, &mut code).expect("to generate firehose json code");
+    }
+    if schema.outputs.arrow_code {
+        arrow::generate_arrow_code(&schema, &mut code).expect("to generate arrow code");
     }
 
     code.push('}'); //impl
 
+    if let Some(literal) = export_dir {
+        if let Some(extension) = exports.unresolved().first() {
+            return Err(compile_error(&literal, format_args!("'export_dir' cannot export '{extension}' as it depends on 'nested' struct, which is only known when generated code is compiled")));
+        }
+        let path = manifest_relative_path(&literal);
+        if cfg!(proc_macro) {
+            if let Err(error) = exports.write(&path, &table_name) {
+                return Err(compile_error(&literal, format_args!("Unable to export outputs into '{}': {error}", path.display())));
+            }
+        }
+    }
+
+    if let Some((path, nested_check)) = baseline_path {
+        //Re-run derive whenever baseline changes
+        let _ = writeln!(code, "\nconst _: &[u8] = include_bytes!({:?});", path.to_string_lossy());
+        code.push_str(&nested_check);
+    }
+
+    if schema.outputs.firehose_partition_code {
+        let _ = firehose::generate_firehose_partition_error(&schema, &mut code);
+    }
+
+    if schema.outputs.parquet_sink {
+        let record_type = format!("{}{}", ident, quote::quote!(#type_gen));
+        parquet::generate_parquet_sink_code(&schema, &record_type, &quote::quote!(#impl_gen).to_string(), &quote::quote!(#where_clause).to_string(), &mut code)
+            .expect("to generate parquet code");
+    }
+
+    if schema.outputs.runtime {
+        runtime::generate_shema_impl(&schema, &self_type, &quote::quote!(#impl_gen).to_string(), &quote::quote!(#where_clause).to_string(), &mut code)
+            .expect("to generate runtime code");
+    }
+
     if schema.outputs.parquet_code {
         let _ = writeln!(
             code,
@@ -467,18 +1346,37 @@ fn from_struct(attributes: &[syn::Attribute], ident: &syn::Ident, generics: &syn
             generics = quote::quote!(#type_gen #where_clause)
         );
 
-        parquet::generate_parquet_writer_interface_code(&schema, &mut code)
+        parquet::generate_parquet_writer_interface_code(&self_type, &schema, &mut code)
             .expect("to generate parquet code");
         let _ = writeln!(code, "}}");
     }
-    code.parse().expect("valid code")
+
+    if schema.outputs.parquet_reader_code {
+        let _ = writeln!(
+            code,
+            "\nimpl{} parquet::record::RecordReader<{ident}{generics}> for Vec<{ident}{generics}> {{",
+            quote::quote!(#impl_gen),
+            ident = ident,
+            generics = quote::quote!(#type_gen #where_clause)
+        );
+
+        parquet::generate_parquet_reader_interface_code(&self_type, &mut code)
+            .expect("to generate parquet code");
+        let _ = writeln!(code, "}}");
+    }
+    Ok(code)
 }
 
+#[cfg(proc_macro)]
 #[proc_macro_derive(Shema, attributes(shema))]
 pub fn shema(input: TokenStream) -> TokenStream {
     let ast: syn::DeriveInput = syn::parse(input).unwrap();
-    match ast.data {
+    let code = match ast.data {
         syn::Data::Struct(data) => from_struct(&ast.attrs, &ast.ident, &ast.generics, &data),
-        _ => compile_error(&ast.ident, "Unsupported type of input. Expected struct"),
+        _ => Err(compile_error(&ast.ident, "Unsupported type of input. Expected struct")),
+    };
+    match code {
+        Ok(code) => code.parse().expect("valid code"),
+        Err(error) => error.to_compile_error().into(),
     }
 }
@@ -1,12 +1,61 @@
-use std::{fmt, io};
-use crate::{TableSchema, Field, FieldType, FieldFlag};
+use core::fmt;
+use super::{TableSchema, Field, FieldType, FieldFlag, TimestampEncoding};
+use super::utils::{self, ConstExpr};
 
 const TAB: &'static str = "  ";
 
+impl TimestampEncoding {
+    #[inline(always)]
+    pub fn parquet_physical_type(&self) -> &'static str {
+        match self {
+            Self::Int96 => "INT96",
+            Self::Millis | Self::Micros | Self::Nanos => "INT64",
+        }
+    }
+
+    #[inline(always)]
+    pub fn parquet_logical_type(&self) -> Option<&'static str> {
+        match self {
+            Self::Int96 => None,
+            Self::Millis => Some("TIMESTAMP(MILLIS,true)"),
+            Self::Micros => Some("TIMESTAMP(MICROS,true)"),
+            Self::Nanos => Some("TIMESTAMP(NANOS,true)"),
+        }
+    }
+
+    #[inline(always)]
+    pub fn parquet_time_unit(&self) -> &'static str {
+        match self {
+            Self::Int96 => unreachable!(),
+            Self::Millis => "MILLIS",
+            Self::Micros => "MICROS",
+            Self::Nanos => "NANOS",
+        }
+    }
+
+    #[inline(always)]
+    ///Returns number of nanoseconds in single unit of INT64 timestamp
+    pub fn unit_nanos(&self) -> u32 {
+        match self {
+            Self::Int96 | Self::Nanos => 1,
+            Self::Millis => 1_000_000,
+            Self::Micros => 1_000,
+        }
+    }
+}
+
 impl FieldType {
     #[inline(always)]
-    pub fn is_aws_firehose_parquet_utf8_converted(&self) -> bool {
-        matches!(self, FieldType::String | FieldType::Array | FieldType::Object | FieldType::Enum)
+    pub fn aws_firehose_parquet_converted_type(&self) -> Option<&'static str> {
+        match self {
+            Self::String | Self::Array | Self::Object | Self::Enum => Some("UTF8"),
+            Self::UByte => Some("UINT_8"),
+            Self::UShort => Some("UINT_16"),
+            Self::UInteger => Some("UINT_32"),
+            Self::ULong => Some("UINT_64"),
+            Self::Decimal => Some("DECIMAL(20,0)"),
+            _ => None,
+        }
     }
 
     #[inline(always)]
@@ -14,6 +63,10 @@ impl FieldType {
         match self {
             Self::Byte | Self::Short | Self::Integer => "INT32",
             Self::Long => "INT64",
+            Self::UByte | Self::UShort | Self::UInteger => "INT32",
+            Self::ULong => "INT64",
+            //u64 requires 9 bytes to fit in two's complement form
+            Self::Decimal => "FIXED_LEN_BYTE_ARRAY(9)",
             Self::Float => "FLOAT",
             Self::Double => "DOUBLE",
             Self::String => "BYTE_ARRAY",
@@ -22,256 +75,413 @@ impl FieldType {
             Self::TimestampZ => "INT96",
             //Encode all arrays/objects as strings
             Self::Array | Self::Object | Self::Enum => "BYTE_ARRAY",
+            //Nested struct is a group of its own fields
+            Self::Struct => "group",
         }
     }
 }
 
-pub fn generate_parquet_schema<O: io::Write>(schema: &TableSchema, out: &mut O) -> io::Result<()> {
-    writeln!(out, "message {} {{", schema.lower_cased_table_name())?;
+#[inline(always)]
+fn is_partition_key(field: &Field) -> bool {
+    field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex)
+}
+
+///Generates parquet schema fields, one per line, using placeholders for nested struct's fields
+pub fn generate_parquet_fields(schema: &TableSchema, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    use fmt::Write;
 
     for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
+        if is_partition_key(field) {
             //Partition keys are not written by Firehose data stream
             continue;
         }
 
-
-        write!(out, "{TAB}")?;
         if field.typ_flags.is_type_flag(FieldFlag::Optional) {
-            out.write_all(b"OPTIONAL ")?;
+            out.push_str("OPTIONAL ");
         } else {
             //Hive outputs everything as optional, confirm if `REQUIRED` is fine
-            out.write_all(b"REQUIRED ")?;
+            out.push_str("REQUIRED ");
+        }
+        if let Some(nested) = field.nested.as_ref() {
+            let _ = writeln!(out, "group {} {{", field.table_field_name());
+            out.push_str(TAB);
+            out.push_str(&utils::const_placeholder(consts, format!("<{nested}>::SHEMA_PARQUET_FIELDS"), TAB.len()));
+            out.push_str("\n}\n");
+            continue;
         }
-        write!(out, "{} {}", field.typ.aws_firehose_parquet(), field.table_field_name())?;
-        if field.typ.is_aws_firehose_parquet_utf8_converted() {
-            out.write_all(b" (UTF8)")?;
+        if let Some(element) = field.element {
+            if matches!(field.typ, FieldType::Object) {
+                let _ = writeln!(out, "group {} (MAP) {{", field.table_field_name());
+                let _ = writeln!(out, "{TAB}REPEATED group key_value {{");
+                let _ = writeln!(out, "{TAB}{TAB}REQUIRED BYTE_ARRAY key (UTF8);");
+                let _ = write!(out, "{TAB}{TAB}REQUIRED {} value", element.aws_firehose_parquet());
+            } else {
+                let _ = writeln!(out, "group {} (LIST) {{", field.table_field_name());
+                let _ = writeln!(out, "{TAB}REPEATED group list {{");
+                let _ = write!(out, "{TAB}{TAB}REQUIRED {} element", element.aws_firehose_parquet());
+            }
+            if let Some(converted_type) = element.aws_firehose_parquet_converted_type() {
+                let _ = write!(out, " ({converted_type})");
+            }
+            let _ = writeln!(out, ";\n{TAB}}}\n}}");
+            continue;
+        }
+        let (physical_type, converted_type) = match field.typ {
+            FieldType::TimestampZ => (field.timestamp.parquet_physical_type(), field.timestamp.parquet_logical_type()),
+            typ => (typ.aws_firehose_parquet(), typ.aws_firehose_parquet_converted_type()),
+        };
+        let _ = write!(out, "{} {}", physical_type, field.table_field_name());
+        if let Some(converted_type) = converted_type {
+            let _ = write!(out, " ({converted_type})");
         }
 
-        out.write_all(b";\n")?;
+        out.push_str(";\n");
     }
+}
+
+pub fn generate_parquet_schema(schema: &TableSchema, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    use fmt::Write;
 
-    out.write_all(b"}")?;
-    out.flush()
+    let mut fields = String::new();
+    generate_parquet_fields(schema, &mut fields, consts);
+
+    let _ = writeln!(out, "message {} {{", schema.lower_cased_table_name());
+    out.push_str(&utils::indent_lines(&fields, TAB, consts));
+    out.push('}');
 }
 
-struct ParquetFieldWriter<'a>(&'a Field);
+///Writes statement pushing `item` reference into `vals`
+struct ParquetValueWriter<'a> {
+    typ: FieldType,
+    timestamp: TimestampEncoding,
+    field_name: &'a str,
+}
 
-impl fmt::Display for ParquetFieldWriter<'_> {
+impl<'a> ParquetValueWriter<'a> {
     #[inline]
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        const ENCODE_HIVE_INT96_TIMESTAMP: &'static str = r#"
-                let julian_day = record.to_julian_day() as u32;
-                let time = time::Duration::hours(record.hour() as _) + time::Duration::minutes(record.minute() as _) + time::Duration::seconds(record.second() as _) + time::Duration::nanoseconds(record.nanosecond() as _);
-
-                let time_nanos = time.whole_nanoseconds() as u64;
-                let time_nanos: [u8; 8] = time_nanos.to_le_bytes();
-                let mut timestamp = ::parquet::data_type::Int96::new();
-                timestamp.set_data(
-                    u32::from_ne_bytes([time_nanos[0], time_nanos[1], time_nanos[2], time_nanos[3]]),
-                    u32::from_ne_bytes([time_nanos[4], time_nanos[5], time_nanos[6], time_nanos[7]]),
-                    julian_day.to_le()
-                );
-
-                vals.push(timestamp);
-        "#;
-        //Parquet writes data in sequence, and uses definition_levels to determine if data is present
-        if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-            fmt.write_fmt(format_args!("let definition_levels = records.iter().map(|rec| if rec.{field_name}.is_some() {{ 1 }} else {{ 0 }}).collect::<Vec<i16>>();", field_name=self.0.original_name))?;
-            fmt.write_str("let definition_levels = Some(definition_levels.as_slice());\n")?;
-        } else {
-            fmt.write_str("let definition_levels = None::<&[i16]>;\n")?;
+    fn new(field: &'a Field, typ: FieldType) -> Self {
+        Self {
+            typ,
+            timestamp: field.timestamp,
+            field_name: field.original_name.as_str(),
         }
+    }
 
-        let column_writer = match self.0.typ {
-            FieldType::Boolean => {
-                if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-                    fmt.write_fmt(format_args!(r#"
-            let vals = records.iter().filter_map(|rec| rec.{field_name}.map(|field| field as bool)).collect::<Vec<_>>();
-                "#, field_name=self.0.original_name))?;
-                } else {
-                    fmt.write_fmt(format_args!(r#"
-            let vals = records.iter().map(|rec| rec.{field_name} as bool).collect::<Vec<_>>();"#, field_name = self.0.original_name))?;
-                }
-
-                "BoolColumnWriter"
-            },
+    #[inline]
+    fn column_writer(&self) -> &'static str {
+        match self.typ {
+            FieldType::Boolean => "BoolColumnWriter",
             //All small integers are encoded as int32
-            FieldType::Byte | FieldType::Short | FieldType::Integer => {
-                if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-                    fmt.write_fmt(format_args!(r#"
-            let vals = records.iter().filter_map(|rec| rec.{field_name}.map(|field| field as i32)).collect::<Vec<_>>();"#, field_name=self.0.original_name))?;
-                } else {
-                    fmt.write_fmt(format_args!(
-                        r#"
-            let vals = records.iter().map(|rec| rec.{field_name} as i32).collect::<Vec<_>>();"#, field_name = self.0.original_name))?;
-                }
-
-                "Int32ColumnWriter"
-            },
-            FieldType::Long => {
-                if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-                    fmt.write_fmt(format_args!(r#"
-            let vals = records.iter().filter_map(|rec| rec.{field_name}.map(|field| field as i64)).collect::<Vec<_>>();"#, field_name=self.0.original_name))?;
-                } else {
-                    fmt.write_fmt(format_args!(
-                        r#"
-            let vals = records.iter().map(|rec| rec.{field_name} as i64).collect::<Vec<_>>();"#, field_name = self.0.original_name))?;
-                }
-
-                "Int64ColumnWriter"
-            },
-            FieldType::Float => {
-                if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-                    fmt.write_fmt(format_args!(r#"
-            let vals = records.iter().filter_map(|rec| rec.{field_name}.map(|field| field as f32)).collect::<Vec<_>>();"#, field_name=self.0.original_name))?;
-                } else {
-                    fmt.write_fmt(format_args!(
-                        r#"
-            let vals = records.iter().map(|rec| rec.{field_name} as f32).collect::<Vec<_>>();"#, field_name = self.0.original_name))?;
-                }
-
-                "FloatColumnWriter"
+            FieldType::Byte | FieldType::Short | FieldType::Integer | FieldType::UByte | FieldType::UShort | FieldType::UInteger => "Int32ColumnWriter",
+            FieldType::Long | FieldType::ULong => "Int64ColumnWriter",
+            FieldType::Decimal => "FixedLenByteArrayColumnWriter",
+            FieldType::Float => "FloatColumnWriter",
+            FieldType::Double => "DoubleColumnWriter",
+            FieldType::TimestampZ => match self.timestamp {
+                TimestampEncoding::Int96 => "Int96ColumnWriter",
+                TimestampEncoding::Millis | TimestampEncoding::Micros | TimestampEncoding::Nanos => "Int64ColumnWriter",
             },
-            FieldType::Double => {
-                if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-                    fmt.write_fmt(format_args!(r#"
-            let vals = records.iter().filter_map(|rec| rec.{field_name}.map(|field| field as f64)).collect::<Vec<_>>();"#, field_name=self.0.original_name))?;
-                } else {
-                    fmt.write_fmt(format_args!(
-                        r#"
-            let vals = records.iter().map(|rec| rec.{field_name} as f64).collect::<Vec<_>>();"#, field_name = self.0.original_name))?;
-                }
-
-                "DoubleColumnWriter"
-            },
-            FieldType::String => {
-                if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-                    fmt.write_fmt(format_args!(r#"
-            let vals = records.iter().filter_map(|rec| rec.{field_name}.as_ref().map(|field| field.as_bytes().into())).collect::<Vec<_>>();"#, field_name=self.0.original_name))?;
-                } else {
-                    fmt.write_fmt(format_args!(r#"
-            let vals = records.iter().map(|rec| rec.{field_name}.as_bytes().into()).collect::<Vec<_>>();"#, field_name=self.0.original_name))?;
-                }
-
-                "ByteArrayColumnWriter"
-            },
-            //Firehose's Hive serializer encodes it as INT96
-            FieldType::TimestampZ => {
-                if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-                    fmt.write_fmt(format_args!(
-                        r#"
-            let mut vals = Vec::new();
-            for record in records.iter() {{
-                if let Some(record) = record.{field_name}.as_ref() {{
-                    {ENCODE_HIVE_INT96_TIMESTAMP}
-                }}
-            }}"#, field_name = self.0.original_name))?;
-                } else {
-                    fmt.write_fmt(format_args!(
-                        r#"
-            let mut vals = Vec::new();
-            for record in records.iter() {{
-                let record = &record.{field_name};
-                {ENCODE_HIVE_INT96_TIMESTAMP}
-            }}"#, field_name = self.0.original_name))?;
-                }
+            FieldType::String | FieldType::Array | FieldType::Object | FieldType::Enum => "ByteArrayColumnWriter",
+            FieldType::Struct => unreachable!("nested struct is written by its own derive"),
+        }
+    }
+}
 
-                "Int96ColumnWriter"
-            },
+impl fmt::Display for ParquetValueWriter<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field_name = self.field_name;
+        match self.typ {
+            FieldType::Boolean => fmt.write_str("vals.push(*item);"),
+            //u32 is stored as its bit pattern, which is how UINT_32 is defined
+            FieldType::Byte | FieldType::Short | FieldType::Integer | FieldType::UByte | FieldType::UShort | FieldType::UInteger => fmt.write_str("vals.push(*item as i32);"),
+            FieldType::Long => fmt.write_str("vals.push(*item as i64);"),
+            //u64 must fit into bigint as it is declared in Glue schema
+            FieldType::ULong => fmt.write_fmt(format_args!(r#"match i64::try_from(*item) {{
+                        Ok(item) => vals.push(item),
+                        Err(_) => return Err(::parquet::errors::ParquetError::General(format!("Column '{field_name}' value {{item}} overflows i64").into()))
+                    }}"#)),
+            //DECIMAL(20,0) is written as big-endian two's complement
+            FieldType::Decimal => fmt.write_str(r#"let item = (*item as u128).to_be_bytes();
+                    vals.push(::parquet::data_type::FixedLenByteArray::from(item[7..].to_vec()));"#),
+            FieldType::Float => fmt.write_str("vals.push(*item as f32);"),
+            FieldType::Double => fmt.write_str("vals.push(*item as f64);"),
+            FieldType::String => fmt.write_str("vals.push(item.as_bytes().into());"),
+            FieldType::TimestampZ if self.timestamp != TimestampEncoding::Int96 => fmt.write_fmt(format_args!(r#"match i64::try_from(item.unix_timestamp_nanos().div_euclid({unit_nanos})) {{
+                        Ok(timestamp) => vals.push(timestamp),
+                        Err(_) => return Err(::parquet::errors::ParquetError::General("Column '{field_name}' timestamp cannot fit INT64".into())),
+                    }}"#, unit_nanos=self.timestamp.unit_nanos())),
+            //Firehose's Hive serializer encodes it as INT96
+            FieldType::TimestampZ => fmt.write_str(r#"let julian_day = item.to_julian_day() as u32;
+                    let time = time::Duration::hours(item.hour() as _) + time::Duration::minutes(item.minute() as _) + time::Duration::seconds(item.second() as _) + time::Duration::nanoseconds(item.nanosecond() as _);
+
+                    let time_nanos = time.whole_nanoseconds() as u64;
+                    let time_nanos: [u8; 8] = time_nanos.to_le_bytes();
+                    let mut timestamp = ::parquet::data_type::Int96::new();
+                    timestamp.set_data(
+                        u32::from_ne_bytes([time_nanos[0], time_nanos[1], time_nanos[2], time_nanos[3]]),
+                        u32::from_ne_bytes([time_nanos[4], time_nanos[5], time_nanos[6], time_nanos[7]]),
+                        julian_day.to_le()
+                    );
+
+                    vals.push(timestamp);"#),
             //Encode all arrays/objects as JSON strings
-            FieldType::Array | FieldType::Object | FieldType::Enum => {
-                if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
-                    fmt.write_fmt(format_args!(r#"
-            let mut vals = Vec::new();
-            for record in records.iter() {{
-                if let Some(record) = record.{field_name}.as_ref() {{
-                    match serde_json::to_vec(record) {{
-                        Ok(rec) => vals.push(rec.into()),
+            FieldType::Array | FieldType::Object | FieldType::Enum => fmt.write_fmt(format_args!(r#"match serde_json::to_vec(item) {{
+                        Ok(item) => vals.push(item.into()),
                         Err(error) => return Err(::parquet::errors::ParquetError::General(format!("Column '{field_name}' cannot be serialized: {{error}}").into()))
-                    }}
-                }}
-            }}"#, field_name=self.0.original_name))?;
-                } else {
-                    fmt.write_fmt(format_args!(r#"
+                    }}"#)),
+            FieldType::Struct => unreachable!("nested struct is written by its own derive"),
+        }
+    }
+}
+
+//Parquet writes data in sequence, and uses definition_levels to determine if data is present
+//Every record comes with definition level of its group, or lower level if group is null
+struct ParquetFieldWriter<'a>(&'a Field);
+
+impl fmt::Display for ParquetFieldWriter<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field_name = self.0.original_name.as_str();
+        let value = ParquetValueWriter::new(self.0, self.0.typ);
+
+        let record = if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
+            format!(r#"Some(record) => match record.{field_name}.as_ref() {{
+                    Some(item) => {{
+                        {value}
+                        definition_levels.push(*level + 1);
+                    }},
+                    None => definition_levels.push(*level),
+                }},"#)
+        } else {
+            format!(r#"Some(record) => {{
+                    let item = &record.{field_name};
+                    {value}
+                    definition_levels.push(*level);
+                }},"#)
+        };
+
+        fmt.write_fmt(format_args!(r#"
             let mut vals = Vec::new();
-            for record in records.iter() {{
-                match serde_json::to_vec(&record.{field_name}) {{
-                    Ok(rec) => vals.push(rec.into()),
-                    Err(error) => return Err(::parquet::errors::ParquetError::General(format!("Column '{field_name}' cannot be serialized: {{error}}").into()))
+            let mut definition_levels = Vec::<i16>::with_capacity(records.len());
+            for (record, level) in records.iter() {{
+                match record {{
+                {record}
+                None => definition_levels.push(*level),
                 }}
-            }}"#, field_name=self.0.original_name))?;
-                }
+            }}
+            if let ColumnWriter::{column_writer}(typed) = column_writer.untyped() {{
+                typed.write_batch(&vals[..], Some(&definition_levels), None)?;
+            }} else {{
+                return Err(::parquet::errors::ParquetError::General("Column '{field_name}' expects {field_type:?} but got another type".into()));
+            }}"#, column_writer=value.column_writer(), field_type=self.0.typ))
+    }
+}
 
-                "ByteArrayColumnWriter"
-            }
+struct ParquetNativeWriter<'a> {
+    field: &'a Field,
+    element: FieldType,
+    //Pattern to bind `item` within collection's iterator
+    item: &'static str,
+}
+
+impl fmt::Display for ParquetNativeWriter<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field_name = self.field.original_name.as_str();
+        let value = ParquetValueWriter::new(self.field, self.element);
+
+        //Empty collection is defined up to repeated group, while null is defined up to optional field
+        let (record, max_definition_level) = if self.field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            (format!(r#"let record = match record.and_then(|record| record.{field_name}.as_ref()) {{
+                    Some(record) => record,
+                    None => {{
+                        definition_levels.push(*level);
+                        repetition_levels.push(0);
+                        continue;
+                    }}
+                }};"#), 2)
+        } else {
+            (format!(r#"let record = match record {{
+                    Some(record) => &record.{field_name},
+                    None => {{
+                        definition_levels.push(*level);
+                        repetition_levels.push(0);
+                        continue;
+                    }}
+                }};"#), 1)
         };
 
         fmt.write_fmt(format_args!(r#"
+            let mut vals = Vec::new();
+            let mut definition_levels = Vec::<i16>::new();
+            let mut repetition_levels = Vec::<i16>::new();
+            for (record, level) in records.iter() {{
+                {record}
+                let mut repetition_level = 0;
+                for {item} in record.iter() {{
+                    {value}
+                    definition_levels.push(*level + {max_definition_level});
+                    repetition_levels.push(repetition_level);
+                    repetition_level = 1;
+                }}
+                if repetition_level == 0 {{
+                    definition_levels.push(*level + {empty_definition_level});
+                    repetition_levels.push(0);
+                }}
+            }}
             if let ColumnWriter::{column_writer}(typed) = column_writer.untyped() {{
-                typed.write_batch(&vals[..], definition_levels, None)?;
+                typed.write_batch(&vals[..], Some(&definition_levels), Some(&repetition_levels))?;
             }} else {{
-                return Err(::parquet::errors::ParquetError::General("Column '{field_name}' expects {field_type:?} but got another type".into()));
-            }}"#, field_name=self.0.original_name, field_type=self.0.typ))
+                return Err(::parquet::errors::ParquetError::General("Column '{field_name}' expects {element:?} but got another type".into()));
+            }}"#, item=self.item, element=self.element, column_writer=value.column_writer(), empty_definition_level=max_definition_level - 1))
     }
 }
 
-struct ParquetFieldSchema<'a>(&'a Field);
+struct ParquetPrimitiveSchema<'a> {
+    name: &'a str,
+    typ: FieldType,
+    timestamp: TimestampEncoding,
+    repetition: &'static str,
+}
 
-impl fmt::Display for ParquetFieldSchema<'_> {
+impl fmt::Display for ParquetPrimitiveSchema<'_> {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         const LOGICAL_NONE: &str = "None";
         const LOGICAL_STRING: &str = "Some(::parquet::basic::LogicalType::String)";
 
-        let (logical_type, physical_type) = match self.0.typ {
+        let mut extra = "";
+        let timestamp_logical_type;
+        let (logical_type, physical_type) = match self.typ {
             FieldType::Byte => ("Some(::parquet::basic::LogicalType::Integer { bit_width: 8, is_signed: true })", "INT32"),
             FieldType::Short => ("Some(::parquet::basic::LogicalType::Integer { bit_width: 16, is_signed: true })", "INT32"),
             FieldType::Integer => ("Some(::parquet::basic::LogicalType::Integer { bit_width: 32, is_signed: true })", "INT32"),
             FieldType::Long => ("Some(::parquet::basic::LogicalType::Integer { bit_width: 64, is_signed: true })", "INT64"),
+            FieldType::UByte => ("Some(::parquet::basic::LogicalType::Integer { bit_width: 8, is_signed: false })", "INT32"),
+            FieldType::UShort => ("Some(::parquet::basic::LogicalType::Integer { bit_width: 16, is_signed: false })", "INT32"),
+            FieldType::UInteger => ("Some(::parquet::basic::LogicalType::Integer { bit_width: 32, is_signed: false })", "INT32"),
+            FieldType::ULong => ("Some(::parquet::basic::LogicalType::Integer { bit_width: 64, is_signed: false })", "INT64"),
+            FieldType::Decimal => {
+                extra = ".with_length(9).with_precision(20).with_scale(0)";
+                ("Some(::parquet::basic::LogicalType::Decimal { scale: 0, precision: 20 })", "FIXED_LEN_BYTE_ARRAY")
+            },
             FieldType::Float => (LOGICAL_NONE, "FLOAT"),
             FieldType::Double => (LOGICAL_NONE, "DOUBLE"),
             FieldType::String => (LOGICAL_STRING, "BYTE_ARRAY"),
             FieldType::Boolean => (LOGICAL_NONE, "BOOLEAN"),
             //Firehose's Hive serializer encodes it as INT96
-            FieldType::TimestampZ => (LOGICAL_NONE, "INT96"),
+            FieldType::TimestampZ if self.timestamp == TimestampEncoding::Int96 => (LOGICAL_NONE, "INT96"),
+            FieldType::TimestampZ => {
+                timestamp_logical_type = format!("Some(::parquet::basic::LogicalType::Timestamp {{ is_adjusted_to_u_t_c: true, unit: ::parquet::basic::TimeUnit::{} }})", self.timestamp.parquet_time_unit());
+                (timestamp_logical_type.as_str(), "INT64")
+            },
             //Encode all arrays/objects as strings
             FieldType::Array | FieldType::Object | FieldType::Enum => (LOGICAL_STRING, "BYTE_ARRAY"),
+            FieldType::Struct => unreachable!("nested struct is a group"),
         };
 
+        fmt.write_fmt(format_args!("::parquet::schema::types::Type::primitive_type_builder(\"{name}\", ::parquet::basic::Type::{physical_type}).with_logical_type({logical_type}){extra}.with_repetition({repetition}).build().unwrap().into()", name=self.name, repetition=self.repetition))
+    }
+}
+
+
+struct ParquetFieldSchema<'a>(&'a Field);
+
+impl fmt::Display for ParquetFieldSchema<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const REQUIRED: &str = "::parquet::basic::Repetition::REQUIRED";
+
         let repetition = if self.0.typ_flags.is_type_flag(FieldFlag::Optional) {
             "::parquet::basic::Repetition::OPTIONAL"
         } else {
-            "::parquet::basic::Repetition::REQUIRED"
+            REQUIRED
         };
 
         let name = self.0.table_field_name();
-        fmt.write_fmt(format_args!("::parquet::schema::types::Type::primitive_type_builder(\"{name}\", ::parquet::basic::Type::{physical_type}).with_logical_type({logical_type}).with_repetition({repetition}).build().unwrap().into()"))
+        if let Some(nested) = self.0.nested.as_ref() {
+            return fmt.write_fmt(format_args!("::parquet::schema::types::Type::group_type_builder(\"{name}\").with_repetition({repetition}).with_fields(<{nested}>::shema_parquet_fields()).build().unwrap().into()"));
+        }
+        match self.0.element {
+            //3-level MAP with string keys
+            Some(element) if matches!(self.0.typ, FieldType::Object) => {
+                let key = ParquetPrimitiveSchema { name: "key", typ: FieldType::String, timestamp: self.0.timestamp, repetition: REQUIRED };
+                let value = ParquetPrimitiveSchema { name: "value", typ: element, timestamp: self.0.timestamp, repetition: REQUIRED };
+                fmt.write_fmt(format_args!("::parquet::schema::types::Type::group_type_builder(\"{name}\").with_logical_type(Some(::parquet::basic::LogicalType::Map)).with_repetition({repetition}).with_fields(vec![::parquet::schema::types::Type::group_type_builder(\"key_value\").with_repetition(::parquet::basic::Repetition::REPEATED).with_fields(vec![{key}, {value}]).build().unwrap().into()]).build().unwrap().into()"))
+            },
+            //3-level LIST
+            Some(element) => {
+                let element = ParquetPrimitiveSchema { name: "element", typ: element, timestamp: self.0.timestamp, repetition: REQUIRED };
+                fmt.write_fmt(format_args!("::parquet::schema::types::Type::group_type_builder(\"{name}\").with_logical_type(Some(::parquet::basic::LogicalType::List)).with_repetition({repetition}).with_fields(vec![::parquet::schema::types::Type::group_type_builder(\"list\").with_repetition(::parquet::basic::Repetition::REPEATED).with_fields(vec![{element}]).build().unwrap().into()]).build().unwrap().into()"))
+            },
+            None => ParquetPrimitiveSchema { name, typ: self.0.typ, timestamp: self.0.timestamp, repetition }.fmt(fmt),
+        }
     }
 }
 
-pub fn generate_parquet_writer_interface_code<O: fmt::Write>(
+///Generates inherent methods to write columns and build schema fields, shared by `RecordWriter` and parent structs
+pub fn generate_parquet_writer_columns_code<O: fmt::Write>(
     schema: &TableSchema,
     out: &mut O,
 ) -> fmt::Result {
+    use super::TAB;
+
     //writer
     writeln!(
         out,
         r#"
-    fn write_to_row_group<W: ::std::io::Write + Send>(&self, row_group_writer: &mut ::parquet::file::writer::SerializedRowGroupWriter<'_, W>) -> ::core::result::Result<(), ::parquet::errors::ParquetError> {{
+    #[doc(hidden)]
+    ///Writes columns of records, each paired with definition level of its group
+    pub fn shema_parquet_write_columns<W: ::std::io::Write + Send>(records: &[(Option<&Self>, i16)], row_group_writer: &mut ::parquet::file::writer::SerializedRowGroupWriter<'_, W>) -> ::core::result::Result<(), ::parquet::errors::ParquetError> {{
         use ::parquet::column::writer::ColumnWriter;
-
-        let mut row_group_writer = row_group_writer;
-        let records = &self;
 "#)?;
 
     for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
+        if is_partition_key(field) {
             continue;
         }
-        writeln!(
-            out,
-            r#"
+
+        if let Some(nested) = field.nested.as_ref() {
+            let record = if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+                format!(r#"Some(record) => match record.{field_name}.as_ref() {{
+                Some(nested) => (Some(nested), *level + 1),
+                None => (None, *level),
+            }},"#, field_name=field.original_name)
+            } else {
+                format!("Some(record) => (Some(&record.{field_name}), *level),", field_name=field.original_name)
+            };
+            writeln!(
+                out,
+                r#"
+        //write '{field_name}' group
+        let nested = records.iter().map(|(record, level)| match record {{
+            {record}
+            None => (None, *level),
+        }}).collect::<Vec<_>>();
+        <{nested}>::shema_parquet_write_columns(&nested, row_group_writer)?;
+        "#, field_name = field.table_field_name())?;
+            continue;
+        }
+
+        let mut columns = [None, None];
+        match field.element {
+            Some(element) if matches!(field.typ, FieldType::Object) => {
+                columns[0] = Some((format!("{}.key_value.key", field.table_field_name()), ParquetNativeWriter { field, element: FieldType::String, item: "(item, _)" }.to_string()));
+                columns[1] = Some((format!("{}.key_value.value", field.table_field_name()), ParquetNativeWriter { field, element, item: "(_, item)" }.to_string()));
+            },
+            Some(element) => {
+                columns[0] = Some((format!("{}.list.element", field.table_field_name()), ParquetNativeWriter { field, element, item: "item" }.to_string()));
+            },
+            None => {
+                columns[0] = Some((field.table_field_name().to_owned(), ParquetFieldWriter(field).to_string()));
+            }
+        }
+
+        for (field_name, writer) in columns.into_iter().flatten() {
+            writeln!(
+                out,
+                r#"
         //write '{field_name}' column
         if let Some(mut column_writer) = row_group_writer.next_column()? {{
             {writer}
@@ -279,7 +489,8 @@ pub fn generate_parquet_writer_interface_code<O: fmt::Write>(
         }} else {{
             return Err(::parquet::errors::ParquetError::General("Failed to get '{field_name}' column".into()));
         }}
-        "#, field_name = field.table_field_name(), writer = ParquetFieldWriter(field))?;
+        "#)?;
+        }
     }
 
     writeln!(
@@ -289,19 +500,515 @@ pub fn generate_parquet_writer_interface_code<O: fmt::Write>(
     }}"#
     )?;
 
-    use crate::TAB;
     //schema
-    writeln!(out, "{TAB}fn schema(&self) -> ::core::result::Result<::parquet::schema::types::TypePtr, ::parquet::errors::ParquetError> {{")?;
+    writeln!(out, "{TAB}#[doc(hidden)]")?;
+    writeln!(out, "{TAB}///Returns parquet schema's fields")?;
+    writeln!(out, "{TAB}pub fn shema_parquet_fields() -> Vec<::parquet::schema::types::TypePtr> {{")?;
     writeln!(out, "{TAB}{TAB}let mut fields = Vec::<::parquet::schema::types::TypePtr>::new();\n")?;
     for field in schema.fields.iter() {
-        if field.typ_flags.is_type_flag(FieldFlag::Index) && !field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex) {
+        if is_partition_key(field) {
             continue;
         }
         writeln!(out, "{TAB}{TAB}//write '{field_name}' column", field_name=field.table_field_name())?;
         writeln!(out, "{TAB}{TAB}fields.push({});", ParquetFieldSchema(field))?;
     }
+    writeln!(out, "{TAB}{TAB}fields")?;
+    writeln!(out, "{TAB}}}")
+}
+
+pub fn generate_parquet_writer_interface_code<O: fmt::Write>(
+    self_type: &str,
+    schema: &TableSchema,
+    out: &mut O,
+) -> fmt::Result {
+    use super::TAB;
 
-    writeln!(out, "\n{TAB}{TAB}::parquet::schema::types::Type::group_type_builder(\"{name}\").with_fields(fields).build().map(Into::into)", name=schema.lower_cased_table_name())?;
+    //writer
+    writeln!(
+        out,
+        r#"
+    fn write_to_row_group<W: ::std::io::Write + Send>(&self, row_group_writer: &mut ::parquet::file::writer::SerializedRowGroupWriter<'_, W>) -> ::core::result::Result<(), ::parquet::errors::ParquetError> {{
+        let records = self.iter().map(|record| (Some(record), 0)).collect::<Vec<_>>();
+        <{self_type}>::shema_parquet_write_columns(&records, row_group_writer)
+    }}"#)?;
+
+    //schema
+    writeln!(out, "{TAB}fn schema(&self) -> ::core::result::Result<::parquet::schema::types::TypePtr, ::parquet::errors::ParquetError> {{")?;
+    writeln!(out, "{TAB}{TAB}::parquet::schema::types::Type::group_type_builder(\"{name}\").with_fields(<{self_type}>::shema_parquet_fields()).build().map(Into::into)", name=schema.lower_cased_table_name())?;
     writeln!(out, "{TAB}}}")?;
     Ok(())
 }
+
+struct ParquetFieldReader<'a> {
+    typ: FieldType,
+    timestamp: TimestampEncoding,
+    field_name: &'a str,
+}
+
+impl<'a> ParquetFieldReader<'a> {
+    #[inline]
+    fn new(field: &'a Field) -> Self {
+        Self {
+            typ: field.typ,
+            timestamp: field.timestamp,
+            field_name: field.table_field_name(),
+        }
+    }
+
+    #[inline]
+    fn element(field: &'a Field, typ: FieldType) -> Self {
+        Self {
+            typ,
+            timestamp: field.timestamp,
+            field_name: field.table_field_name(),
+        }
+    }
+
+    #[inline]
+    fn data_type(&self) -> &'static str {
+        match self.typ {
+            FieldType::Boolean => "BoolType",
+            FieldType::Byte | FieldType::Short | FieldType::Integer | FieldType::UByte | FieldType::UShort | FieldType::UInteger => "Int32Type",
+            FieldType::Long | FieldType::ULong => "Int64Type",
+            FieldType::Decimal => "FixedLenByteArrayType",
+            FieldType::Float => "FloatType",
+            FieldType::Double => "DoubleType",
+            FieldType::TimestampZ => match self.timestamp {
+                TimestampEncoding::Int96 => "Int96Type",
+                TimestampEncoding::Millis | TimestampEncoding::Micros | TimestampEncoding::Nanos => "Int64Type",
+            },
+            FieldType::String | FieldType::Array | FieldType::Object | FieldType::Enum => "ByteArrayType",
+            FieldType::Struct => unreachable!("nested struct is read by its own derive"),
+        }
+    }
+}
+
+impl fmt::Display for ParquetFieldReader<'_> {
+    #[inline]
+    ///Writes expression converting parquet's `value` into field's type
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field_name = self.field_name;
+        match self.typ {
+            FieldType::Boolean | FieldType::Float | FieldType::Double => fmt.write_str("value"),
+            //u32 is stored as its bit pattern
+            FieldType::UInteger => fmt.write_str("value as u32"),
+            FieldType::Byte | FieldType::Short | FieldType::Integer | FieldType::Long | FieldType::UByte | FieldType::UShort | FieldType::ULong => fmt.write_fmt(format_args!(r#"match ::core::convert::TryFrom::try_from(value) {{
+                    Ok(value) => value,
+                    Err(_) => return Err(::parquet::errors::ParquetError::General(format!("Column '{field_name}' value {{value}} is out of range").into())),
+                }}"#)),
+            FieldType::Decimal => fmt.write_fmt(format_args!(r#"match value.data().iter().try_fold(0u64, |acc, byte| acc.checked_mul(256)?.checked_add(*byte as u64)).map(::core::convert::TryFrom::try_from) {{
+                    Some(Ok(value)) => value,
+                    _ => return Err(::parquet::errors::ParquetError::General("Column '{field_name}' value is out of range".into())),
+                }}"#)),
+            FieldType::String => fmt.write_fmt(format_args!(r#"match String::from_utf8(value.data().to_vec()) {{
+                    Ok(value) => value,
+                    Err(error) => return Err(::parquet::errors::ParquetError::General(format!("Column '{field_name}' is not valid UTF-8: {{error}}").into())),
+                }}"#)),
+            FieldType::TimestampZ if self.timestamp != TimestampEncoding::Int96 => fmt.write_fmt(format_args!(r#"match time::OffsetDateTime::from_unix_timestamp_nanos(value as i128 * {unit_nanos}) {{
+                    Ok(value) => value,
+                    Err(error) => return Err(::parquet::errors::ParquetError::General(format!("Column '{field_name}' has invalid timestamp: {{error}}").into())),
+                }}"#, unit_nanos=self.timestamp.unit_nanos())),
+            //Firehose's Hive serializer encodes it as INT96
+            FieldType::TimestampZ => fmt.write_fmt(format_args!(r#"{{
+                    let data = value.data();
+                    let time_nanos = (data[1] as u64) << 32 | data[0] as u64;
+                    match time::Date::from_julian_day(data[2] as i32) {{
+                        Ok(date) => time::OffsetDateTime::new_utc(date, time::Time::MIDNIGHT) + time::Duration::nanoseconds(time_nanos as i64),
+                        Err(error) => return Err(::parquet::errors::ParquetError::General(format!("Column '{field_name}' has invalid date: {{error}}").into())),
+                    }}
+                }}"#)),
+            //All arrays/objects are stored as JSON strings
+            FieldType::Array | FieldType::Object | FieldType::Enum => fmt.write_fmt(format_args!(r#"match serde_json::from_slice(value.data()) {{
+                    Ok(value) => value,
+                    Err(error) => return Err(::parquet::errors::ParquetError::General(format!("Column '{field_name}' cannot be deserialized: {{error}}").into())),
+                }}"#)),
+            FieldType::Struct => unreachable!("nested struct is read by its own derive"),
+        }
+    }
+}
+
+///Generates inherent method to read columns, shared by `RecordReader` and parent structs
+pub fn generate_parquet_reader_columns_code<O: fmt::Write>(
+    schema: &TableSchema,
+    out: &mut O,
+) -> fmt::Result {
+    writeln!(
+        out,
+        r#"
+    #[doc(hidden)]
+    ///Reads records from columns under `prefix`, each paired with its definition level
+    ///
+    ///Record is null when its definition level is below `level`
+    pub fn shema_parquet_read_columns(row_group_reader: &mut dyn ::parquet::file::reader::RowGroupReader, prefix: &str, level: i16, num_records: usize) -> ::core::result::Result<Vec<(i16, Option<Self>)>, ::parquet::errors::ParquetError> {{"#)?;
+
+    if schema.fields.iter().any(|field| !is_partition_key(field) && field.element.is_none() && field.nested.is_none()) {
+        writeln!(
+            out,
+            r#"        //Reads column by name, returning None for every null value
+        fn read_column<T: ::parquet::data_type::DataType>(row_group_reader: &mut dyn ::parquet::file::reader::RowGroupReader, name: &str, num_records: usize) -> ::core::result::Result<Vec<(i16, Option<T::T>)>, ::parquet::errors::ParquetError> {{
+            let idx = match row_group_reader.metadata().columns().iter().position(|column| column.column_path().string() == name) {{
+                Some(idx) => idx,
+                None => return Err(::parquet::errors::ParquetError::General(format!("Column '{{name}}' is missing").into())),
+            }};
+            let max_def_level = row_group_reader.metadata().column(idx).column_descr().max_def_level();
+            let mut column_reader = match T::get_column_reader(row_group_reader.get_column_reader(idx)?) {{
+                Some(column_reader) => column_reader,
+                None => return Err(::parquet::errors::ParquetError::General(format!("Column '{{name}}' expects {{}} but got another type", T::get_physical_type()).into())),
+            }};
+
+            let mut definition_levels = Vec::new();
+            let mut vals = Vec::new();
+            let (records, _, _) = column_reader.read_records(num_records, Some(&mut definition_levels), None, &mut vals)?;
+            if records != num_records {{
+                return Err(::parquet::errors::ParquetError::General(format!("Column '{{name}}' has {{records}} records but expected {{num_records}}").into()));
+            }}
+
+            if max_def_level == 0 {{
+                Ok(vals.into_iter().map(|value| (0, Some(value))).collect())
+            }} else {{
+                let mut vals = vals.into_iter();
+                Ok(definition_levels.iter().map(|level| (*level, if *level == max_def_level {{ vals.next() }} else {{ None }})).collect())
+            }}
+        }}
+"#)?;
+    }
+
+    if schema.fields.iter().any(|field| !is_partition_key(field) && field.element.is_some()) {
+        writeln!(
+            out,
+            r#"        //Reads repeated column of LIST or MAP by name, returning None for every null collection
+        fn read_repeated_column<T: ::parquet::data_type::DataType>(row_group_reader: &mut dyn ::parquet::file::reader::RowGroupReader, name: &str, num_records: usize) -> ::core::result::Result<Vec<(i16, Option<Vec<T::T>>)>, ::parquet::errors::ParquetError> {{
+            let idx = match row_group_reader.metadata().columns().iter().position(|column| column.column_path().string() == name) {{
+                Some(idx) => idx,
+                None => return Err(::parquet::errors::ParquetError::General(format!("Column '{{name}}' is missing").into())),
+            }};
+            let max_def_level = row_group_reader.metadata().column(idx).column_descr().max_def_level();
+            let mut column_reader = match T::get_column_reader(row_group_reader.get_column_reader(idx)?) {{
+                Some(column_reader) => column_reader,
+                None => return Err(::parquet::errors::ParquetError::General(format!("Column '{{name}}' expects {{}} but got another type", T::get_physical_type()).into())),
+            }};
+
+            let mut definition_levels = Vec::new();
+            let mut repetition_levels = Vec::new();
+            let mut vals = Vec::new();
+            let (records, _, _) = column_reader.read_records(num_records, Some(&mut definition_levels), Some(&mut repetition_levels), &mut vals)?;
+            if records != num_records {{
+                return Err(::parquet::errors::ParquetError::General(format!("Column '{{name}}' has {{records}} records but expected {{num_records}}").into()));
+            }}
+
+            let mut vals = vals.into_iter();
+            let mut result = Vec::with_capacity(num_records);
+            for (definition_level, repetition_level) in definition_levels.iter().zip(repetition_levels.iter()) {{
+                //Collection is present when defined up to repeated group
+                if *repetition_level == 0 {{
+                    result.push((*definition_level, if *definition_level + 1 >= max_def_level {{ Some(Vec::new()) }} else {{ None }}));
+                }}
+                if *definition_level == max_def_level {{
+                    match (result.last_mut(), vals.next()) {{
+                        (Some((_, Some(collection))), Some(value)) => collection.push(value),
+                        _ => return Err(::parquet::errors::ParquetError::General(format!("Column '{{name}}' has invalid levels").into())),
+                    }}
+                }}
+            }}
+            Ok(result)
+        }}
+"#)?;
+    }
+
+    for (idx, field) in schema.fields.iter().enumerate() {
+        if is_partition_key(field) {
+            continue;
+        }
+        if let Some(nested) = field.nested.as_ref() {
+            let level = if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+                "level + 1"
+            } else {
+                "level"
+            };
+            writeln!(
+                out,
+                "        let mut column_{idx} = <{nested}>::shema_parquet_read_columns(row_group_reader, &format!(\"{{prefix}}{field_name}.\"), {level}, num_records)?.into_iter();",
+                field_name = field.table_field_name(),
+            )?;
+            continue;
+        }
+        match field.element {
+            Some(element) if matches!(field.typ, FieldType::Object) => {
+                writeln!(
+                    out,
+                    "        let mut column_{idx}_key = read_repeated_column::<::parquet::data_type::ByteArrayType>(row_group_reader, &format!(\"{{prefix}}{field_name}.key_value.key\"), num_records)?.into_iter();",
+                    field_name = field.table_field_name(),
+                )?;
+                writeln!(
+                    out,
+                    "        let mut column_{idx} = read_repeated_column::<::parquet::data_type::{data_type}>(row_group_reader, &format!(\"{{prefix}}{field_name}.key_value.value\"), num_records)?.into_iter();",
+                    data_type = ParquetFieldReader::element(field, element).data_type(),
+                    field_name = field.table_field_name(),
+                )?;
+            },
+            Some(element) => writeln!(
+                out,
+                "        let mut column_{idx} = read_repeated_column::<::parquet::data_type::{data_type}>(row_group_reader, &format!(\"{{prefix}}{field_name}.list.element\"), num_records)?.into_iter();",
+                data_type = ParquetFieldReader::element(field, element).data_type(),
+                field_name = field.table_field_name(),
+            )?,
+            None => writeln!(
+                out,
+                "        let mut column_{idx} = read_column::<::parquet::data_type::{data_type}>(row_group_reader, &format!(\"{{prefix}}{field_name}\"), num_records)?.into_iter();",
+                data_type = ParquetFieldReader::new(field).data_type(),
+                field_name = field.table_field_name(),
+            )?,
+        }
+    }
+
+    writeln!(out, "\n        let mut result = Vec::with_capacity(num_records);")?;
+    writeln!(out, "        for _ in 0..num_records {{")?;
+    //Record is defined as deep as its least defined column
+    writeln!(out, "            let mut definition_level = i16::MAX;")?;
+    for (idx, field) in schema.fields.iter().enumerate() {
+        if is_partition_key(field) {
+            continue;
+        }
+        if matches!(field.typ, FieldType::Object) && field.element.is_some() {
+            writeln!(
+                out,
+                r#"            let (level_{idx}, column_{idx}_value) = match (column_{idx}_key.next().unwrap_or_default(), column_{idx}.next().unwrap_or_default()) {{
+                ((level, Some(keys)), (_, Some(values))) if keys.len() == values.len() => (level, Some(keys.into_iter().zip(values))),
+                ((level, None), (_, None)) => (level, None),
+                _ => return Err(::parquet::errors::ParquetError::General("Column '{field_name}' has mismatching keys and values".into())),
+            }};"#,
+                field_name = field.table_field_name()
+            )?;
+        } else {
+            writeln!(out, "            let (level_{idx}, column_{idx}_value) = column_{idx}.next().unwrap_or_default();")?;
+        }
+        writeln!(out, "            definition_level = ::core::cmp::min(definition_level, level_{idx});")?;
+    }
+    writeln!(
+        out,
+        r#"            if definition_level < level {{
+                result.push((definition_level, None));
+                continue;
+            }}"#
+    )?;
+
+    for (idx, field) in schema.fields.iter().enumerate() {
+        if is_partition_key(field) {
+            //Partition keys are not written by Firehose data stream
+            writeln!(out, "            let {} = Default::default();", field.original_name)?;
+        } else if field.nested.is_some() {
+            if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+                writeln!(out, "            let {} = column_{idx}_value;", field.original_name)?;
+            } else {
+                writeln!(
+                    out,
+                    r#"            let {field} = match column_{idx}_value {{
+                Some(value) => value,
+                None => return Err(::parquet::errors::ParquetError::General("Column '{field_name}' is required but has null value".into())),
+            }};"#,
+                    field = field.original_name,
+                    field_name = field.table_field_name(),
+                )?;
+            }
+        } else if let Some(element) = field.element {
+            let collect = if matches!(field.typ, FieldType::Object) {
+                format!(r#"let key = {{
+                        let value = key;
+                        {key_reader}
+                    }};
+                    items.push((key, {reader}));"#, key_reader = ParquetFieldReader::element(field, FieldType::String), reader = ParquetFieldReader::element(field, element))
+            } else {
+                format!("items.push({});", ParquetFieldReader::element(field, element))
+            };
+            let item = if matches!(field.typ, FieldType::Object) {
+                "(key, value)"
+            } else {
+                "value"
+            };
+            let (present, missing) = if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+                ("Some(items.into_iter().collect())".to_owned(), "None".to_owned())
+            } else {
+                ("items.into_iter().collect()".to_owned(), format!("return Err(::parquet::errors::ParquetError::General(\"Column '{}' is required but has null value\".into()))", field.table_field_name()))
+            };
+            writeln!(
+                out,
+                r#"            let {field} = match column_{idx}_value {{
+                Some(collection) => {{
+                    let mut items = Vec::new();
+                    for {item} in collection {{
+                    {collect}
+                    }}
+                    {present}
+                }},
+                None => {missing},
+            }};"#,
+                field = field.original_name,
+            )?;
+        } else if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            writeln!(
+                out,
+                r#"            let {field} = match column_{idx}_value {{
+                Some(value) => Some({reader}),
+                None => None,
+            }};"#,
+                field = field.original_name,
+                reader = ParquetFieldReader::new(field)
+            )?;
+        } else {
+            writeln!(
+                out,
+                r#"            let {field} = match column_{idx}_value {{
+                Some(value) => {reader},
+                None => return Err(::parquet::errors::ParquetError::General("Column '{field_name}' is required but has null value".into())),
+            }};"#,
+                field = field.original_name,
+                field_name = field.table_field_name(),
+                reader = ParquetFieldReader::new(field)
+            )?;
+        }
+    }
+
+    write!(out, "            result.push((definition_level, Some(Self {{")?;
+    for field in schema.fields.iter() {
+        write!(out, " {},", field.original_name)?;
+    }
+    writeln!(out, " }})));")?;
+    writeln!(out, "        }}\n")?;
+    writeln!(out, "        Ok(result)")?;
+    writeln!(out, "    }}")
+}
+
+pub fn generate_parquet_reader_interface_code<O: fmt::Write>(
+    self_type: &str,
+    out: &mut O,
+) -> fmt::Result {
+    writeln!(
+        out,
+        r#"
+    fn read_from_row_group(&mut self, row_group_reader: &mut dyn ::parquet::file::reader::RowGroupReader, num_records: usize) -> ::core::result::Result<(), ::parquet::errors::ParquetError> {{
+        let num_records = ::core::cmp::min(num_records, row_group_reader.metadata().num_rows() as usize);
+        self.reserve(num_records);
+
+        for (_, record) in <{self_type}>::shema_parquet_read_columns(row_group_reader, "", 0, num_records)? {{
+            match record {{
+                Some(record) => self.push(record),
+                None => return Err(::parquet::errors::ParquetError::General("Record is null".into())),
+            }}
+        }}
+
+        Ok(())
+    }}"#)
+}
+
+///Generates sink type writing records into local directory, partitioned as Firehose does
+pub fn generate_parquet_sink_code<O: fmt::Write>(schema: &TableSchema, record_type: &str, impl_gen: &str, where_clause: &str, out: &mut O) -> fmt::Result {
+    let name = &schema.name;
+    let table_name = schema.lower_cased_table_name();
+    writeln!(out, r#"
+///Writes `{name}` records into local directory, laid out as Firehose delivers them to S3
+///
+///Records are grouped by partition keys into `<root>/<shema_firehose_s3_path_prefix()>` directories,
+///where each write appends row group to current file of partition.
+///File is finalized once it reaches limit of rows or bytes, after which remaining records start new file.
+pub struct {name}ParquetSink {{
+    root: ::std::path::PathBuf,
+    properties: ::parquet::file::properties::WriterPropertiesPtr,
+    max_rows: usize,
+    max_bytes: usize,
+    //Unix time in milliseconds to distinguish files of different sinks
+    created: u128,
+    sequence: usize,
+    files: ::std::collections::HashMap<String, (::std::path::PathBuf, ::parquet::file::writer::SerializedFileWriter<::std::fs::File>, usize)>,
+    finished: Vec<::std::path::PathBuf>,
+}}
+
+impl {name}ParquetSink {{
+    ///Creates new sink, writing into `root` directory without limits on size of files
+    pub fn new(root: impl Into<::std::path::PathBuf>, properties: ::parquet::file::properties::WriterPropertiesPtr) -> Self {{
+        Self {{
+            root: root.into(),
+            properties,
+            max_rows: usize::MAX,
+            max_bytes: usize::MAX,
+            created: ::std::time::SystemTime::now().duration_since(::std::time::UNIX_EPOCH).map(|time| time.as_millis()).unwrap_or_default(),
+            sequence: 0,
+            files: ::std::collections::HashMap::new(),
+            finished: Vec::new(),
+        }}
+    }}
+
+    ///Sets number of rows after which file is finalized
+    pub fn with_max_rows(mut self, max_rows: usize) -> Self {{
+        self.max_rows = max_rows;
+        self
+    }}
+
+    ///Sets number of bytes after which file is finalized
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {{
+        self.max_bytes = max_bytes;
+        self
+    }}
+
+    ///Writes `records` as row group per partition, splitting it between files to stay within limit of rows
+    ///
+    ///Fails without writing anything if any record's partition is not valid
+    pub fn write{impl_gen}(&mut self, records: &[{record_type}]) -> ::core::result::Result<(), ::parquet::errors::ParquetError> {where_clause} {{
+        //Partitions are ordered for predictable naming of files
+        let mut groups = ::std::collections::BTreeMap::<String, Vec<(Option<&{record_type}>, i16)>>::new();
+        for record in records.iter() {{
+            //Invalid partition value could write outside of root or into different directory
+            if let Err(error) = record.shema_validate_firehose_s3_path_prefix() {{
+                return Err(::parquet::errors::ParquetError::General(error.to_string()));
+            }}
+            groups.entry(record.shema_firehose_s3_path_prefix().to_string()).or_default().push((Some(record), 0));
+        }}
+
+        for (prefix, group) in groups {{
+            let mut group = group.as_slice();
+            while !group.is_empty() {{
+                let (_, writer, rows) = match self.files.entry(prefix.clone()) {{
+                    ::std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                    ::std::collections::hash_map::Entry::Vacant(entry) => {{
+                        let dir = self.root.join(&prefix);
+                        ::std::fs::create_dir_all(&dir)?;
+                        let path = dir.join(format!("{table_name}-{{}}-{{:05}}.parquet", self.created, self.sequence));
+                        self.sequence += 1;
+
+                        let empty: &[{record_type}] = &[];
+                        let schema = ::parquet::record::RecordWriter::schema(&empty)?;
+                        let file = ::std::fs::File::create_new(&path)?;
+                        let writer = ::parquet::file::writer::SerializedFileWriter::new(file, schema, self.properties.clone())?;
+                        entry.insert((path, writer, 0))
+                    }},
+                }};
+
+                //File always has room for at least single row, as it is finalized once limit is reached
+                let (chunk, rest) = group.split_at(group.len().min(self.max_rows.saturating_sub(*rows).max(1)));
+                let mut row_group = writer.next_row_group()?;
+                <{record_type}>::shema_parquet_write_columns(chunk, &mut row_group)?;
+                row_group.close()?;
+                *rows += chunk.len();
+                group = rest;
+
+                if *rows >= self.max_rows || writer.bytes_written() >= self.max_bytes {{
+                    if let Some((path, writer, _)) = self.files.remove(&prefix) {{
+                        writer.close()?;
+                        self.finished.push(path);
+                    }}
+                }}
+            }}
+        }}
+        Ok(())
+    }}
+
+    ///Finalizes all files, returning paths of files written since creation
+    pub fn close(mut self) -> ::core::result::Result<Vec<::std::path::PathBuf>, ::parquet::errors::ParquetError> {{
+        for (_, (path, writer, _)) in self.files.drain() {{
+            writer.close()?;
+            self.finished.push(path);
+        }}
+        self.finished.sort();
+        Ok(self.finished)
+    }}
+}}"#)
+}
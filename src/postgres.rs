@@ -0,0 +1,86 @@
+use core::fmt::{self, Write};
+use std::borrow::Cow;
+
+use super::{TableSchema, Field, FieldType, FieldFlag};
+use super::utils::SqlIdentifier;
+
+const TAB: &'static str = "    ";
+
+impl FieldType {
+    #[inline(always)]
+    pub fn postgres_type(&self) -> &'static str {
+        match self {
+            //There is no single byte integer
+            Self::Byte | Self::Short | Self::UByte => "smallint",
+            Self::Integer | Self::UShort => "integer",
+            Self::Long | Self::UInteger | Self::ULong => "bigint",
+            Self::Decimal => "numeric(20,0)",
+            Self::Float => "real",
+            Self::Double => "double precision",
+            Self::String => "text",
+            Self::Boolean => "boolean",
+            Self::TimestampZ => "timestamptz",
+            //Everything serialized as json goes into jsonb including nested structs
+            Self::Array | Self::Object | Self::Enum | Self::Struct => "jsonb",
+        }
+    }
+}
+
+impl Field {
+    #[inline]
+    fn postgres_type(&self) -> Cow<'static, str> {
+        match self.element {
+            //Native arrays are supported for primitive types only
+            Some(element) if matches!(self.typ, FieldType::Array) => format!("{}[]", element.postgres_type()).into(),
+            _ => self.typ.postgres_type().into(),
+        }
+    }
+}
+
+//Postgres escapes single quote by repeating it
+struct PostgresLiteral<'a>(&'a str);
+
+impl fmt::Display for PostgresLiteral<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_char('\'')?;
+        for ch in self.0.chars() {
+            if ch == '\'' {
+                fmt.write_char('\'')?;
+            }
+            fmt.write_char(ch)?;
+        }
+        fmt.write_char('\'')
+    }
+}
+
+pub fn generate_postgres_ddl(schema: &TableSchema, out: &mut String) {
+    let table_name = schema.lower_cased_table_name();
+    let table = SqlIdentifier(&table_name);
+
+    let _ = writeln!(out, "CREATE TABLE IF NOT EXISTS {table} (");
+    for (idx, field) in schema.fields.iter().enumerate() {
+        let _ = write!(out, "{TAB}{} {}", SqlIdentifier(field.table_field_name()), field.postgres_type());
+        if !field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            out.push_str(" NOT NULL");
+        }
+        if idx + 1 < schema.fields.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str(");");
+
+    for field in schema.fields.iter() {
+        if field.typ_flags.is_type_flag(FieldFlag::Index) {
+            let name = field.table_field_name();
+            let index = format!("{table_name}_{name}_idx");
+            let _ = write!(out, "\nCREATE INDEX IF NOT EXISTS {} ON {table} ({});", SqlIdentifier(&index), SqlIdentifier(name));
+        }
+    }
+
+    for field in schema.fields.iter() {
+        if !field.docstring.is_empty() {
+            let _ = write!(out, "\nCOMMENT ON COLUMN {table}.{} IS {};", SqlIdentifier(field.table_field_name()), PostgresLiteral(&field.docstring));
+        }
+    }
+}
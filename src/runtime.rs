@@ -0,0 +1,51 @@
+use core::fmt::{self, Write};
+
+use super::{TableSchema, FieldType, FieldFlag};
+
+const TAB: &'static str = "    ";
+
+impl FieldType {
+    #[inline(always)]
+    fn runtime_kind(&self) -> &'static str {
+        match self {
+            Self::Byte => "Byte",
+            Self::Short => "Short",
+            Self::Integer => "Integer",
+            Self::Long => "Long",
+            Self::UByte => "UByte",
+            Self::UShort => "UShort",
+            Self::UInteger => "UInteger",
+            Self::ULong => "ULong",
+            Self::Decimal => "Decimal",
+            Self::Float => "Float",
+            Self::Double => "Double",
+            Self::String => "String",
+            Self::Boolean => "Boolean",
+            Self::TimestampZ => "TimestampZ",
+            Self::Array => "Array",
+            Self::Object => "Object",
+            Self::Enum => "Enum",
+            Self::Struct => "Struct",
+        }
+    }
+}
+
+///Generates implementation of `shema_runtime::Shema`
+pub fn generate_shema_impl(schema: &TableSchema, record_type: &str, impl_gen: &str, where_clause: &str, out: &mut String) -> fmt::Result {
+    writeln!(out, "\nimpl{impl_gen} ::shema_runtime::Shema for {record_type} {where_clause} {{")?;
+    writeln!(out, "{TAB}const TABLE_NAME: &'static str = Self::SHEMA_TABLE_NAME;")?;
+    writeln!(out, "{TAB}const FIELDS: &'static [::shema_runtime::FieldInfo] = &[")?;
+    for field in schema.fields.iter() {
+        writeln!(out, "{TAB}{TAB}::shema_runtime::FieldInfo {{")?;
+        writeln!(out, "{TAB}{TAB}{TAB}name: {:?},", field.table_field_name())?;
+        writeln!(out, "{TAB}{TAB}{TAB}rust_name: {:?},", field.original_name.strip_prefix("r#").unwrap_or(&field.original_name))?;
+        writeln!(out, "{TAB}{TAB}{TAB}kind: ::shema_runtime::FieldKind::{},", field.typ.runtime_kind())?;
+        writeln!(out, "{TAB}{TAB}{TAB}optional: {},", field.typ_flags.is_type_flag(FieldFlag::Optional))?;
+        writeln!(out, "{TAB}{TAB}{TAB}index: {},", field.typ_flags.is_type_flag(FieldFlag::Index))?;
+        writeln!(out, "{TAB}{TAB}{TAB}date_index: {},", field.typ_flags.is_type_flag(FieldFlag::FirehoseDateIndex))?;
+        writeln!(out, "{TAB}{TAB}{TAB}doc: {:?},", field.docstring)?;
+        writeln!(out, "{TAB}{TAB}}},")?;
+    }
+    writeln!(out, "{TAB}];")?;
+    writeln!(out, "}}")
+}
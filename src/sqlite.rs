@@ -0,0 +1,122 @@
+use core::fmt::{self, Write};
+
+use super::{TableSchema, Field, FieldType, FieldFlag};
+use super::utils::SqlIdentifier;
+
+const TAB: &'static str = "    ";
+
+impl FieldType {
+    #[inline(always)]
+    pub fn sqlite_type(&self) -> &'static str {
+        match self {
+            Self::Byte | Self::Short | Self::Integer | Self::Long => "INTEGER",
+            Self::UByte | Self::UShort | Self::UInteger | Self::ULong => "INTEGER",
+            //u64 cannot fit INTEGER so it is stored as decimal string
+            Self::Decimal => "TEXT",
+            Self::Float | Self::Double => "REAL",
+            Self::Boolean => "INTEGER",
+            //RFC3339 string
+            Self::TimestampZ => "TEXT",
+            Self::String => "TEXT",
+            //Everything else is stored as json string
+            Self::Array | Self::Object | Self::Enum | Self::Struct => "TEXT",
+        }
+    }
+}
+
+pub fn generate_sqlite_ddl(schema: &TableSchema, out: &mut String) {
+    let table_name = schema.lower_cased_table_name();
+    let table = SqlIdentifier(&table_name);
+
+    let _ = writeln!(out, "CREATE TABLE IF NOT EXISTS {table} (");
+    for (idx, field) in schema.fields.iter().enumerate() {
+        let _ = write!(out, "{TAB}{} {}", SqlIdentifier(field.table_field_name()), field.typ.sqlite_type());
+        if !field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            out.push_str(" NOT NULL");
+        }
+        if idx + 1 < schema.fields.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str(");");
+
+    for field in schema.fields.iter() {
+        if field.typ_flags.is_type_flag(FieldFlag::Index) {
+            let name = field.table_field_name();
+            let index = format!("{table_name}_{name}_idx");
+            let _ = write!(out, "\nCREATE INDEX IF NOT EXISTS {} ON {table} ({});", SqlIdentifier(&index), SqlIdentifier(name));
+        }
+    }
+}
+
+pub fn generate_sqlite_insert(schema: &TableSchema, out: &mut String) {
+    let _ = write!(out, "INSERT INTO {} (", SqlIdentifier(&schema.lower_cased_table_name()));
+    for (idx, field) in schema.fields.iter().enumerate() {
+        if idx > 0 {
+            out.push_str(", ");
+        }
+        let _ = write!(out, "{}", SqlIdentifier(field.table_field_name()));
+    }
+    out.push_str(") VALUES (");
+    for idx in 1..=schema.fields.len() {
+        if idx > 1 {
+            out.push_str(", ");
+        }
+        let _ = write!(out, "?{idx}");
+    }
+    out.push(')');
+}
+
+//Writes expression producing value to bind, converting `value` reference when necessary
+struct SqliteValue<'a>(&'a Field);
+
+impl fmt::Display for SqliteValue<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CONVERSION_FAILURE: &str = "map_err(|error| ::rusqlite::Error::ToSqlConversionFailure(Box::new(error)))";
+
+        //Native collections have no sqlite counterpart
+        let typ = if self.0.element.is_some() {
+            FieldType::Array
+        } else {
+            self.0.typ
+        };
+        match typ {
+            FieldType::Byte | FieldType::Short | FieldType::Integer | FieldType::UByte | FieldType::UShort | FieldType::UInteger => fmt.write_str("*value"),
+            FieldType::Float | FieldType::Double | FieldType::Boolean => fmt.write_str("*value"),
+            FieldType::Long => fmt.write_str("*value as i64"),
+            FieldType::ULong => fmt.write_fmt(format_args!("i64::try_from(*value).{CONVERSION_FAILURE}?")),
+            FieldType::Decimal => fmt.write_str("value.to_string()"),
+            FieldType::String => fmt.write_str("value"),
+            FieldType::TimestampZ => fmt.write_fmt(format_args!("value.format(&time::format_description::well_known::Rfc3339).{CONVERSION_FAILURE}?")),
+            FieldType::Array | FieldType::Object | FieldType::Enum | FieldType::Struct => fmt.write_fmt(format_args!("serde_json::to_string(value).{CONVERSION_FAILURE}?")),
+        }
+    }
+}
+
+pub fn generate_sqlite_code<O: fmt::Write>(schema: &TableSchema, out: &mut O) -> fmt::Result {
+    writeln!(out, "{TAB}///Binds all fields as parameters of `SHEMA_SQLITE_INSERT` statement")?;
+    writeln!(out, "{TAB}pub fn shema_sqlite_bind(&self, statement: &mut ::rusqlite::Statement<'_>) -> ::rusqlite::Result<()> {{")?;
+    for (idx, field) in schema.fields.iter().enumerate() {
+        let idx = idx + 1;
+        if field.typ_flags.is_type_flag(FieldFlag::Optional) {
+            writeln!(out, "{TAB}{TAB}match self.{}.as_ref() {{", field.original_name)?;
+            writeln!(out, "{TAB}{TAB}{TAB}Some(value) => statement.raw_bind_parameter({idx}, {})?,", SqliteValue(field))?;
+            writeln!(out, "{TAB}{TAB}{TAB}None => statement.raw_bind_parameter({idx}, ::rusqlite::types::Null)?,")?;
+            writeln!(out, "{TAB}{TAB}}}")?;
+        } else {
+            writeln!(out, "{TAB}{TAB}let value = &self.{};", field.original_name)?;
+            writeln!(out, "{TAB}{TAB}statement.raw_bind_parameter({idx}, {})?;", SqliteValue(field))?;
+        }
+    }
+    writeln!(out, "{TAB}{TAB}Ok(())")?;
+    writeln!(out, "{TAB}}}\n")?;
+
+    writeln!(out, "{TAB}///Inserts record into table using cached `SHEMA_SQLITE_INSERT` statement")?;
+    writeln!(out, "{TAB}pub fn shema_sqlite_insert(&self, connection: &::rusqlite::Connection) -> ::rusqlite::Result<usize> {{")?;
+    writeln!(out, "{TAB}{TAB}let mut statement = connection.prepare_cached(Self::SHEMA_SQLITE_INSERT)?;")?;
+    writeln!(out, "{TAB}{TAB}self.shema_sqlite_bind(&mut statement)?;")?;
+    writeln!(out, "{TAB}{TAB}statement.raw_execute()")?;
+    writeln!(out, "{TAB}}}")
+}
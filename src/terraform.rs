@@ -0,0 +1,91 @@
+use core::fmt::{self, Write};
+
+use super::firehose::{self, FirehoseInput, FirehoseType};
+use super::utils::ConstExpr;
+
+const TAB: &'static str = "  ";
+
+///Double quoted HCL string literal, escaping template sequences
+struct HclLiteral<'a>(&'a str);
+
+impl fmt::Display for HclLiteral<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_char('"')?;
+        let mut chars = self.0.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' | '\\' => {
+                    fmt.write_char('\\')?;
+                    fmt.write_char(ch)?;
+                },
+                '\n' => fmt.write_str("\\n")?,
+                //`${` and `%{` start template interpolation
+                '$' | '%' if chars.peek() == Some(&'{') => {
+                    fmt.write_char(ch)?;
+                    fmt.write_char(ch)?;
+                },
+                ch => fmt.write_char(ch)?,
+            }
+        }
+        fmt.write_char('"')
+    }
+}
+
+fn write_columns(block: &str, columns: &[FirehoseType<'_>], indent: &str, out: &mut String) {
+    for column in columns.iter() {
+        //Align as `terraform fmt` does
+        let pad = if column.comment.is_empty() { "" } else { "   " };
+        let _ = writeln!(out, "\n{indent}{block} {{");
+        let _ = writeln!(out, "{indent}{TAB}name{pad} = {}", HclLiteral(column.name));
+        //Placeholder of nested struct's type has no characters to escape
+        let _ = writeln!(out, "{indent}{TAB}type{pad} = {}", HclLiteral(&column.typ));
+        if !column.comment.is_empty() {
+            let _ = writeln!(out, "{indent}{TAB}comment = {}", HclLiteral(&column.comment));
+        }
+        let _ = writeln!(out, "{indent}}}");
+    }
+}
+
+///Generates `partition_keys` and `storage_descriptor` blocks of `aws_glue_catalog_table`
+pub fn generate_glue_table(input: FirehoseInput<'_>, out: &mut String, consts: &mut Vec<ConstExpr>) {
+    let location = input.schema.athena.location.as_deref();
+    let schema = firehose::firehose_schema(input, consts);
+
+    let _ = writeln!(out, "table_type = \"EXTERNAL_TABLE\"");
+    write_columns("partition_keys", &schema.partition_keys, "", out);
+
+    out.push_str("\nstorage_descriptor {\n");
+    if let Some(location) = location {
+        let _ = writeln!(out, "{TAB}location      = {}", HclLiteral(location));
+    }
+    let _ = writeln!(out, "{TAB}input_format  = \"org.apache.hadoop.hive.ql.io.parquet.MapredParquetInputFormat\"");
+    let _ = writeln!(out, "{TAB}output_format = \"org.apache.hadoop.hive.ql.io.parquet.MapredParquetOutputFormat\"");
+    let _ = writeln!(out, "\n{TAB}ser_de_info {{");
+    let _ = writeln!(out, "{TAB}{TAB}serialization_library = \"org.apache.hadoop.hive.ql.io.parquet.serde.ParquetHiveSerDe\"");
+    let _ = writeln!(out, "{TAB}}}");
+    write_columns("columns", &schema.columns, TAB, out);
+    out.push('}');
+}
+
+///Generates `processing_configuration` block of `aws_kinesis_firehose_delivery_stream` to extract partition keys
+pub fn generate_firehose_processing_configuration(input: FirehoseInput<'_>, out: &mut String) {
+    //Types are irrelevant here
+    let schema = firehose::firehose_schema(input, &mut Vec::new());
+
+    let query = firehose::firehose_jq_query(&schema);
+
+    out.push_str("processing_configuration {\n");
+    let _ = writeln!(out, "{TAB}enabled = true");
+    let _ = writeln!(out, "\n{TAB}processors {{");
+    let _ = writeln!(out, "{TAB}{TAB}type = \"MetadataExtraction\"");
+    let _ = writeln!(out, "\n{TAB}{TAB}parameters {{");
+    let _ = writeln!(out, "{TAB}{TAB}{TAB}parameter_name  = \"MetadataExtractionQuery\"");
+    let _ = writeln!(out, "{TAB}{TAB}{TAB}parameter_value = {}", HclLiteral(&query));
+    let _ = writeln!(out, "{TAB}{TAB}}}");
+    let _ = writeln!(out, "\n{TAB}{TAB}parameters {{");
+    let _ = writeln!(out, "{TAB}{TAB}{TAB}parameter_name  = \"JsonParsingEngine\"");
+    let _ = writeln!(out, "{TAB}{TAB}{TAB}parameter_value = \"JQ-1.6\"");
+    let _ = writeln!(out, "{TAB}{TAB}}}");
+    let _ = writeln!(out, "{TAB}}}");
+    out.push('}');
+}
@@ -1,4 +1,56 @@
 
+use core::fmt::{self, Write};
+
+///Single quoted string literal escaped with backslash as in Hive and ClickHouse
+pub struct SqlLiteral<'a>(pub &'a str);
+
+impl fmt::Display for SqlLiteral<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_char('\'')?;
+        for ch in self.0.chars() {
+            match ch {
+                '\'' | '\\' => {
+                    fmt.write_char('\\')?;
+                    fmt.write_char(ch)?;
+                },
+                '\n' => fmt.write_str("\\n")?,
+                ch => fmt.write_char(ch)?,
+            }
+        }
+        fmt.write_char('\'')
+    }
+}
+
+///Double quoted SQL identifier, escaping double quote by repeating it as in Postgres and SQLite
+pub struct SqlIdentifier<'a>(pub &'a str);
+
+impl fmt::Display for SqlIdentifier<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_char('"')?;
+        for ch in self.0.chars() {
+            if ch == '"' {
+                fmt.write_char('"')?;
+            }
+            fmt.write_char(ch)?;
+        }
+        fmt.write_char('"')
+    }
+}
+
+///Raw string literal of generated code, using enough `#` to contain any `"#` sequence of value
+pub struct RawStrLiteral<'a>(pub &'a str);
+
+impl fmt::Display for RawStrLiteral<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hashes = 1;
+        for (idx, _) in self.0.match_indices('"') {
+            let len = self.0[idx + 1..].bytes().take_while(|byte| *byte == b'#').count();
+            hashes = hashes.max(len + 1);
+        }
+        write!(fmt, "r{0:#<1$}\"{2}\"{0:#<1$}", "", hashes, self.0)
+    }
+}
+
 pub fn to_lower_case_by_sep(input: &str, sep: char) -> String {
     let mut output = String::with_capacity(input.len());
 
@@ -56,3 +108,124 @@ pub fn to_lower_case_by_sep(input: &str, sep: char) -> String {
 
     output
 }
+
+///Character to mark placeholder of constant expression within generated string
+const CONST_PLACEHOLDER: char = '\u{E000}';
+
+///Constant string expression that is only known when generated code is compiled
+pub struct ConstExpr {
+    pub expr: String,
+    //Number of spaces to insert after every new line of expression's value
+    pub indent: usize,
+}
+
+///Pushes `expr` into `consts`, returning placeholder to be written in its place
+pub fn const_placeholder(consts: &mut Vec<ConstExpr>, expr: String, indent: usize) -> String {
+    let placeholder = format!("{CONST_PLACEHOLDER}{}{CONST_PLACEHOLDER}", consts.len());
+    consts.push(ConstExpr {
+        expr,
+        indent,
+    });
+    placeholder
+}
+
+#[inline(always)]
+///Returns whether `value` contains placeholder of constant expression
+pub fn has_const_placeholder(value: &str) -> bool {
+    value.contains(CONST_PLACEHOLDER)
+}
+
+///Prefixes every non-empty line of `input` with `indent`, adjusting indentation of `consts` accordingly
+pub fn indent_lines(input: &str, indent: &str, consts: &mut [ConstExpr]) -> String {
+    let mut output = String::with_capacity(input.len());
+    for line in input.split_inclusive('\n') {
+        if line != "\n" {
+            output.push_str(indent);
+        }
+        output.push_str(line);
+    }
+
+    for expr in consts.iter_mut() {
+        expr.indent += indent.len();
+    }
+    output
+}
+
+///Writes `json` into `out`, removing quotes around placeholders of json values and indenting them accordingly
+pub fn write_json_unquoting_consts(json: &str, out: &mut String, consts: &mut [ConstExpr]) {
+    const PLACEHOLDER_LEN: usize = CONST_PLACEHOLDER.len_utf8();
+
+    for line in json.split_inclusive('\n') {
+        match line.find(&format!("\"{CONST_PLACEHOLDER}")) {
+            Some(start) => {
+                let indent = line.len() - line.trim_start().len();
+                let end = start + 1 + PLACEHOLDER_LEN + line[start + 1 + PLACEHOLDER_LEN..].find(CONST_PLACEHOLDER).expect("placeholder to be closed");
+                let idx = line[start + 1 + PLACEHOLDER_LEN..end].parse::<usize>().expect("valid placeholder");
+                consts[idx].indent = indent;
+                out.push_str(&line[..start]);
+                out.push_str(&line[start + 1..end + PLACEHOLDER_LEN]);
+                //Skip closing quote
+                out.push_str(&line[end + PLACEHOLDER_LEN + 1..]);
+            },
+            None => out.push_str(line),
+        }
+    }
+}
+
+///Writes `value` as string constant's expression, concatenating `consts` in place of their placeholders at compile time
+pub fn write_const_str(out: &mut String, value: &str, consts: &[ConstExpr]) {
+    use core::fmt::Write;
+
+    if consts.is_empty() {
+        let _ = write!(out, "{}", RawStrLiteral(value));
+        return;
+    }
+
+    out.push_str("{\n        const PARTS: &[(&str, usize)] = &[");
+    for (idx, part) in value.split(CONST_PLACEHOLDER).enumerate() {
+        //Placeholders are enclosed by marker, hence every odd part is index
+        if idx % 2 == 0 {
+            let _ = write!(out, "({}, 0), ", RawStrLiteral(part));
+        } else {
+            let expr = &consts[part.parse::<usize>().expect("valid placeholder")];
+            let _ = write!(out, "({}, {}), ", expr.expr, expr.indent);
+        }
+    }
+    out.push_str(r#"];
+        const fn shema_concat<const LEN: usize>(parts: &[(&str, usize)]) -> ([u8; LEN], usize) {
+            let mut result = [0u8; LEN];
+            let mut len = 0;
+            let mut idx = 0;
+            while idx < parts.len() {
+                let (part, indent) = parts[idx];
+                let part = part.as_bytes();
+                let mut byte_idx = 0;
+                while byte_idx < part.len() {
+                    if len < LEN {
+                        result[len] = part[byte_idx];
+                    }
+                    len += 1;
+                    if part[byte_idx] == b'\n' && byte_idx + 1 < part.len() {
+                        let mut indent_idx = 0;
+                        while indent_idx < indent {
+                            if len < LEN {
+                                result[len] = b' ';
+                            }
+                            len += 1;
+                            indent_idx += 1;
+                        }
+                    }
+                    byte_idx += 1;
+                }
+                idx += 1;
+            }
+            (result, len)
+        }
+        const LEN: usize = shema_concat::<0>(PARTS).1;
+        const RESULT: [u8; LEN] = shema_concat::<LEN>(PARTS).0;
+        match ::core::str::from_utf8(&RESULT) {
+            Ok(result) => result,
+            Err(_) => panic!("shema generated invalid utf-8 string"),
+        }
+    }"#);
+}
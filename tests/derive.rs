@@ -95,6 +95,40 @@ fn should_verify_firehose_partition_and_serialization() {
     writer.close().expect("to finalize parquet");
 }
 
+//Writes `records` as single row group of parquet file
+fn write_parquet<T>(records: &[T]) -> Result<Vec<u8>, parquet::errors::ParquetError> where for<'a> &'a [T]: parquet::record::RecordWriter<T> {
+    let schema = parquet::record::RecordWriter::schema(&records)?;
+    let props = parquet::file::properties::WriterProperties::builder().build();
+
+    let mut buffer = Vec::new();
+    let mut writer = parquet::file::writer::SerializedFileWriter::new(&mut buffer, schema, props.into())?;
+    let mut row_group = writer.next_row_group()?;
+    parquet::record::RecordWriter::write_to_row_group(&records, &mut row_group)?;
+    row_group.close()?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+//Reads records of the first row group of parquet file
+fn read_parquet<T>(buffer: Vec<u8>) -> Vec<T> where Vec<T>: parquet::record::RecordReader<T> {
+    use parquet::file::reader::FileReader;
+
+    let reader = parquet::file::serialized_reader::SerializedFileReader::new(bytes::Bytes::from(buffer)).expect("to create reader");
+    let mut row_group = reader.get_row_group(0).expect("to have row group");
+    let rows = row_group.metadata().num_rows() as usize;
+    let mut result = Vec::new();
+    parquet::record::RecordReader::read_from_row_group(&mut result, &mut *row_group, rows).expect("to read");
+    result
+}
+
+//Returns number of rows written into parquet file
+fn parquet_file_rows(path: &std::path::Path) -> i64 {
+    use parquet::file::reader::FileReader;
+
+    let reader = parquet::file::serialized_reader::SerializedFileReader::new(std::fs::File::open(path).expect("to open file")).expect("valid parquet");
+    reader.metadata().file_metadata().num_rows()
+}
+
 #[test]
 fn should_verify_derive() {
     assert_eq!(AnalyticsEvent::SHEMA_TABLE_NAME, "analytics_event");
@@ -248,3 +282,1824 @@ fn should_verify_derive() {
 }"#
     );
 }
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(firehose_schema, firehose_parquet_schema, parquet_code)]
+pub(crate) struct Counters {
+    ubyte: u8,
+    ushort: u16,
+    uint: u32,
+    ulong: u64,
+    usize: usize,
+    optional_ulong: Option<u64>,
+    #[shema(decimal)]
+    big: u64,
+    #[shema(decimal)]
+    optional_big: Option<u64>,
+}
+
+#[test]
+fn should_verify_unsigned_integers() {
+    assert_eq!(
+        Counters::SHEMA_FIREHOSE_PARQUET_SCHEMA,
+        r#"message counters {
+  REQUIRED INT32 ubyte (UINT_8);
+  REQUIRED INT32 ushort (UINT_16);
+  REQUIRED INT32 uint (UINT_32);
+  REQUIRED INT64 ulong (UINT_64);
+  REQUIRED INT64 usize (UINT_64);
+  OPTIONAL INT64 optional_ulong (UINT_64);
+  REQUIRED FIXED_LEN_BYTE_ARRAY(9) big (DECIMAL(20,0));
+  OPTIONAL FIXED_LEN_BYTE_ARRAY(9) optional_big (DECIMAL(20,0));
+}"#);
+    parquet::schema::parser::parse_message_type(Counters::SHEMA_FIREHOSE_PARQUET_SCHEMA).expect("valid parquet schema");
+
+    let glue_schema: serde_json::Value = serde_json::from_str(Counters::SHEMA_FIREHOSE_SCHEMA).expect("valid json");
+    let types = glue_schema["columns"].as_array().unwrap().iter().map(|column| column["type"].as_str().unwrap()).collect::<Vec<_>>();
+    assert_eq!(types, ["smallint", "int", "bigint", "bigint", "bigint", "bigint", "decimal(20,0)", "decimal(20,0)"]);
+
+    let mut events = [Counters {
+        ubyte: u8::MAX,
+        ushort: u16::MAX,
+        uint: u32::MAX,
+        ulong: i64::MAX as u64,
+        usize: 0,
+        optional_ulong: None,
+        big: u64::MAX,
+        optional_big: Some(u64::MAX),
+    }];
+    write_parquet(&events).expect("to write parquet");
+
+    events[0].optional_ulong = Some(u64::MAX);
+    let error = write_parquet(&events).expect_err("to fail on overflow");
+    assert!(error.to_string().contains("'optional_ulong'"));
+}
+
+#[derive(Shema, Debug, PartialEq)]
+#[shema(parquet_code, parquet_reader_code)]
+pub(crate) struct OwnedEvent {
+    #[shema(index)]
+    client_id: String,
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    server_time: Option<time::OffsetDateTime>,
+    user_id: Option<String>,
+    #[shema(json)]
+    #[shema(rename = "extra")]
+    extras: Option<prost_wkt_types::Struct>,
+    #[shema(enumeration)]
+    false_enum: prost_wkt_types::Struct,
+    byte: i8,
+    short: Option<i16>,
+    int: i32,
+    long: i64,
+    ptr: isize,
+    ubyte: u8,
+    uint: u32,
+    ulong: Option<u64>,
+    #[shema(decimal)]
+    big: u64,
+    float: f32,
+    double: Option<f64>,
+    boolean: bool,
+    array: Vec<String>,
+}
+
+#[test]
+fn should_read_written_parquet() {
+    let mut props = prost_wkt_types::Struct::new();
+    props.insert("key".to_owned(), "value".to_owned());
+    let mut events = [
+        OwnedEvent {
+            client_id: "id".to_owned(),
+            client_time: time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2020, 31).unwrap(), time::Time::from_hms_nano(1, 2, 3, 4).unwrap()),
+            server_time: Some(time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2025, 31).unwrap(), time::Time::from_hms(23, 59, 59).unwrap())),
+            user_id: Some("user".to_owned()),
+            extras: Some(props.clone()),
+            false_enum: props,
+            byte: i8::MIN,
+            short: Some(i16::MAX),
+            int: i32::MIN,
+            long: i64::MAX,
+            ptr: -1,
+            ubyte: u8::MAX,
+            uint: u32::MAX,
+            ulong: Some(i64::MAX as u64),
+            big: u64::MAX,
+            float: 0.5,
+            double: Some(10.0),
+            boolean: true,
+            array: vec!["first".to_owned(), "second".to_owned()],
+        },
+        OwnedEvent {
+            client_id: "id".to_owned(),
+            client_time: time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(1970, 1).unwrap(), time::Time::MIDNIGHT),
+            server_time: None,
+            user_id: None,
+            extras: None,
+            false_enum: prost_wkt_types::Struct::new(),
+            byte: 0,
+            short: None,
+            int: 0,
+            long: 0,
+            ptr: 0,
+            ubyte: 0,
+            uint: 0,
+            ulong: None,
+            big: 0,
+            float: 0.0,
+            double: None,
+            boolean: false,
+            array: Vec::new(),
+        },
+    ];
+
+    let result = read_parquet::<OwnedEvent>(write_parquet(&events).expect("to write parquet"));
+
+    //Partition keys are not stored
+    for event in events.iter_mut() {
+        event.client_id.clear();
+    }
+    assert_eq!(result, events);
+}
+
+#[derive(Shema, Debug, PartialEq)]
+#[shema(firehose_parquet_schema, parquet_code, parquet_reader_code, timestamp = "micros")]
+pub(crate) struct TimestampEvent {
+    micros: time::OffsetDateTime,
+    #[shema(timestamp = "millis")]
+    millis: Option<time::OffsetDateTime>,
+    #[shema(timestamp = "nanos")]
+    nanos: time::OffsetDateTime,
+    #[shema(timestamp = "int96")]
+    int96: time::OffsetDateTime,
+}
+
+#[test]
+fn should_verify_timestamp_encoding() {
+    assert_eq!(
+        TimestampEvent::SHEMA_FIREHOSE_PARQUET_SCHEMA,
+        r#"message timestamp_event {
+  REQUIRED INT64 micros (TIMESTAMP(MICROS,true));
+  OPTIONAL INT64 millis (TIMESTAMP(MILLIS,true));
+  REQUIRED INT64 nanos (TIMESTAMP(NANOS,true));
+  REQUIRED INT96 int96;
+}"#);
+    let expected_schema = parquet::schema::parser::parse_message_type(TimestampEvent::SHEMA_FIREHOSE_PARQUET_SCHEMA).expect("valid parquet schema");
+
+    let timestamp = time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(1969, 31).unwrap(), time::Time::from_hms_nano(1, 2, 3, 456_789_123).unwrap());
+    let events = [TimestampEvent {
+        micros: timestamp,
+        millis: Some(timestamp),
+        nanos: timestamp,
+        int96: timestamp,
+    }];
+    let schema = parquet::record::RecordWriter::schema(&events.as_slice()).expect("to get schema");
+    assert_eq!(schema.get_fields(), expected_schema.get_fields());
+
+    let result = read_parquet::<TimestampEvent>(write_parquet(&events).expect("to write parquet"));
+
+    assert_eq!(result, [TimestampEvent {
+        micros: timestamp.replace_nanosecond(456_789_000).unwrap(),
+        millis: Some(timestamp.replace_nanosecond(456_000_000).unwrap()),
+        nanos: timestamp,
+        int96: timestamp,
+    }]);
+}
+
+#[derive(Shema, Debug, PartialEq)]
+#[shema(firehose_parquet_schema, parquet_code, parquet_reader_code)]
+pub(crate) struct NativeEvent {
+    #[shema(native)]
+    tags: Vec<String>,
+    #[shema(native)]
+    ids: Option<std::collections::HashSet<u64>>,
+    #[shema(native)]
+    counters: std::collections::HashMap<String, i32>,
+    #[shema(native)]
+    weights: Option<std::collections::HashMap<String, f64>>,
+    array: Vec<String>,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(firehose_parquet_schema, parquet_nested)]
+pub(crate) struct NestedEvent {
+    tags: Vec<i16>,
+    #[shema(json)]
+    json_tags: Vec<i16>,
+    objects: Vec<prost_wkt_types::Struct>,
+}
+
+#[test]
+fn should_verify_native_collections() {
+    assert_eq!(
+        NativeEvent::SHEMA_FIREHOSE_PARQUET_SCHEMA,
+        r#"message native_event {
+  REQUIRED group tags (LIST) {
+    REPEATED group list {
+      REQUIRED BYTE_ARRAY element (UTF8);
+    }
+  }
+  OPTIONAL group ids (LIST) {
+    REPEATED group list {
+      REQUIRED INT64 element (UINT_64);
+    }
+  }
+  REQUIRED group counters (MAP) {
+    REPEATED group key_value {
+      REQUIRED BYTE_ARRAY key (UTF8);
+      REQUIRED INT32 value;
+    }
+  }
+  OPTIONAL group weights (MAP) {
+    REPEATED group key_value {
+      REQUIRED BYTE_ARRAY key (UTF8);
+      REQUIRED DOUBLE value;
+    }
+  }
+  REQUIRED BYTE_ARRAY array (UTF8);
+}"#);
+    parquet::schema::parser::parse_message_type(NativeEvent::SHEMA_FIREHOSE_PARQUET_SCHEMA).expect("valid parquet schema");
+
+    assert_eq!(
+        NestedEvent::SHEMA_FIREHOSE_PARQUET_SCHEMA,
+        r#"message nested_event {
+  REQUIRED group tags (LIST) {
+    REPEATED group list {
+      REQUIRED INT32 element;
+    }
+  }
+  REQUIRED BYTE_ARRAY json_tags (UTF8);
+  REQUIRED BYTE_ARRAY objects (UTF8);
+}"#);
+
+    let events = [
+        NativeEvent {
+            tags: vec!["first".to_owned(), "second".to_owned()],
+            ids: Some([1, u32::MAX as u64].into_iter().collect()),
+            counters: [("one".to_owned(), 1), ("two".to_owned(), 2)].into_iter().collect(),
+            weights: Some([("one".to_owned(), 0.5)].into_iter().collect()),
+            array: vec!["json".to_owned()],
+        },
+        NativeEvent {
+            tags: Vec::new(),
+            ids: None,
+            counters: Default::default(),
+            weights: None,
+            array: Vec::new(),
+        },
+        NativeEvent {
+            tags: vec!["single".to_owned()],
+            ids: Some(Default::default()),
+            counters: [("three".to_owned(), 3)].into_iter().collect(),
+            weights: Some(Default::default()),
+            array: Vec::new(),
+        },
+    ];
+
+    let result = read_parquet::<NativeEvent>(write_parquet(&events).expect("to write parquet"));
+    assert_eq!(result, events);
+
+    let too_big = [NativeEvent {
+        tags: Vec::new(),
+        ids: Some([u64::MAX].into_iter().collect()),
+        counters: Default::default(),
+        weights: None,
+        array: Vec::new(),
+    }];
+    write_parquet(&too_big).expect_err("to fail on overflow");
+}
+
+#[derive(Shema, Debug, PartialEq)]
+#[shema(firehose_schema, firehose_parquet_schema, parquet_code, parquet_reader_code)]
+pub(crate) struct Location {
+    lat: f64,
+    ///Human readable name
+    name: Option<String>,
+}
+
+#[derive(Shema, Debug, PartialEq)]
+#[shema(firehose_schema, firehose_parquet_schema, parquet_code, parquet_reader_code)]
+pub(crate) struct Device {
+    id: u32,
+    #[shema(nested)]
+    location: Option<Location>,
+    #[shema(native)]
+    tags: Vec<String>,
+}
+
+#[derive(Shema, Debug, PartialEq)]
+#[shema(firehose_schema, firehose_parquet_schema, parquet_code, parquet_reader_code)]
+pub(crate) struct NestedStructEvent {
+    #[shema(index)]
+    client_id: String,
+    #[shema(nested)]
+    device: Device,
+    #[shema(nested, rename = "home_location")]
+    home: Option<Location>,
+}
+
+#[test]
+fn should_verify_nested_structs() {
+    assert_eq!(Location::SHEMA_GLUE_TYPE, "struct<lat:double,name:string>");
+    assert_eq!(
+        NestedStructEvent::SHEMA_FIREHOSE_SCHEMA,
+        r#"{
+  "name": "nested_struct_event",
+  "partition_keys": [
+    {
+      "name": "client_id",
+      "type": "string",
+      "comment": "",
+      "mapping": ".client_id"
+    }
+  ],
+  "columns": [
+    {
+      "name": "device",
+      "type": "struct<id:bigint,location:struct<lat:double,name:string>,tags:array<string>>",
+      "comment": ""
+    },
+    {
+      "name": "home_location",
+      "type": "struct<lat:double,name:string>",
+      "comment": ""
+    }
+  ]
+}"#);
+    assert_eq!(
+        NestedStructEvent::SHEMA_FIREHOSE_PARQUET_SCHEMA,
+        r#"message nested_struct_event {
+  REQUIRED group device {
+    REQUIRED INT32 id (UINT_32);
+    OPTIONAL group location {
+      REQUIRED DOUBLE lat;
+      OPTIONAL BYTE_ARRAY name (UTF8);
+    }
+    REQUIRED group tags (LIST) {
+      REPEATED group list {
+        REQUIRED BYTE_ARRAY element (UTF8);
+      }
+    }
+  }
+  OPTIONAL group home_location {
+    REQUIRED DOUBLE lat;
+    OPTIONAL BYTE_ARRAY name (UTF8);
+  }
+}"#);
+    parquet::schema::parser::parse_message_type(NestedStructEvent::SHEMA_FIREHOSE_PARQUET_SCHEMA).expect("valid parquet schema");
+
+    let events = [
+        NestedStructEvent {
+            client_id: String::new(),
+            device: Device {
+                id: u32::MAX,
+                location: Some(Location { lat: 1.5, name: Some("office".to_owned()) }),
+                tags: vec!["first".to_owned(), "second".to_owned()],
+            },
+            home: Some(Location { lat: -2.0, name: None }),
+        },
+        NestedStructEvent {
+            client_id: String::new(),
+            device: Device {
+                id: 1,
+                location: None,
+                tags: Vec::new(),
+            },
+            home: None,
+        },
+        NestedStructEvent {
+            client_id: String::new(),
+            device: Device {
+                id: 2,
+                location: Some(Location { lat: 0.0, name: None }),
+                tags: vec!["single".to_owned()],
+            },
+            home: Some(Location { lat: 3.0, name: Some("home".to_owned()) }),
+        },
+    ];
+
+    let result = read_parquet::<NestedStructEvent>(write_parquet(&events).expect("to write parquet"));
+    assert_eq!(result, events);
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(athena_ddl, athena_location = "s3://bucket/athena_event/", athena_projection = "2020,2100")]
+#[shema(athena_property = "parquet.compression=SNAPPY")]
+pub(crate) struct AthenaEvent {
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    #[shema(index)]
+    ///Client's id
+    client_id: String,
+    ///User's 'name'
+    name: Option<String>,
+    #[shema(nested)]
+    location: Location,
+    #[shema(json)]
+    props: prost_wkt_types::Struct,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(athena_ddl)]
+pub(crate) struct AthenaCounters {
+    ubyte: u8,
+    ushort: u16,
+    uint: u32,
+    ulong: u64,
+    usize: usize,
+    optional_ulong: Option<u64>,
+    #[shema(decimal)]
+    big: u64,
+    #[shema(decimal)]
+    optional_big: Option<u64>,
+}
+
+#[test]
+fn should_verify_athena_ddl() {
+    assert_eq!(
+        AthenaEvent::SHEMA_ATHENA_DDL,
+        r#"CREATE EXTERNAL TABLE IF NOT EXISTS `athena_event` (
+  `client_time` timestamp,
+  `name` string COMMENT 'User\'s \'name\'',
+  `location` struct<lat:double,name:string>,
+  `props` string
+)
+PARTITIONED BY (
+  `year` string COMMENT 'Extracted from \'client_time\'',
+  `month` string COMMENT 'Extracted from \'client_time\'',
+  `day` string COMMENT 'Extracted from \'client_time\'',
+  `client_id` string COMMENT 'Client\'s id'
+)
+STORED AS PARQUET
+LOCATION 's3://bucket/athena_event/'
+TBLPROPERTIES (
+  'projection.enabled'='true',
+  'projection.year.type'='integer',
+  'projection.year.range'='2020,2100',
+  'projection.year.digits'='4',
+  'projection.month.type'='integer',
+  'projection.month.range'='1,12',
+  'projection.month.digits'='2',
+  'projection.day.type'='integer',
+  'projection.day.range'='1,31',
+  'projection.day.digits'='2',
+  'projection.client_id.type'='injected',
+  'parquet.compression'='SNAPPY'
+)"#);
+
+    assert_eq!(
+        AthenaCounters::SHEMA_ATHENA_DDL,
+        r#"CREATE EXTERNAL TABLE IF NOT EXISTS `athena_counters` (
+  `ubyte` smallint,
+  `ushort` int,
+  `uint` bigint,
+  `ulong` bigint,
+  `usize` bigint,
+  `optional_ulong` bigint,
+  `big` decimal(20,0),
+  `optional_big` decimal(20,0)
+)
+STORED AS PARQUET"#);
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(postgres)]
+pub(crate) struct PostgresEvent {
+    #[shema(index)]
+    ///Owner's account
+    owner: String,
+    #[shema(index)]
+    created_at: time::OffsetDateTime,
+    note: Option<String>,
+    #[shema(json)]
+    payload: prost_wkt_types::Struct,
+    #[shema(enumeration)]
+    state: prost_wkt_types::Struct,
+    small: i8,
+    count: u32,
+    #[shema(decimal)]
+    total: u64,
+    ratio: f32,
+    score: f64,
+    active: bool,
+    history: Vec<String>,
+    #[shema(native)]
+    tags: Vec<String>,
+    #[shema(native)]
+    ids: Option<std::collections::HashSet<u64>>,
+    #[shema(native)]
+    counters: std::collections::HashMap<String, i32>,
+}
+
+#[test]
+fn should_verify_postgres_ddl() {
+    assert_eq!(
+        PostgresEvent::SHEMA_POSTGRES_DDL,
+        r#"CREATE TABLE IF NOT EXISTS "postgres_event" (
+    "owner" text NOT NULL,
+    "created_at" timestamptz NOT NULL,
+    "note" text,
+    "payload" jsonb NOT NULL,
+    "state" jsonb NOT NULL,
+    "small" smallint NOT NULL,
+    "count" bigint NOT NULL,
+    "total" numeric(20,0) NOT NULL,
+    "ratio" real NOT NULL,
+    "score" double precision NOT NULL,
+    "active" boolean NOT NULL,
+    "history" jsonb NOT NULL,
+    "tags" text[] NOT NULL,
+    "ids" bigint[],
+    "counters" jsonb NOT NULL
+);
+CREATE INDEX IF NOT EXISTS "postgres_event_owner_idx" ON "postgres_event" ("owner");
+CREATE INDEX IF NOT EXISTS "postgres_event_created_at_idx" ON "postgres_event" ("created_at");
+COMMENT ON COLUMN "postgres_event"."owner" IS 'Owner''s account';"#);
+}
+
+#[derive(Shema)]
+#[shema(sqlite, sqlite_code)]
+pub(crate) struct CacheEvent {
+    #[shema(index)]
+    client_id: String,
+    client_time: time::OffsetDateTime,
+    user_id: Option<String>,
+    count: u64,
+    #[shema(decimal)]
+    big: u64,
+    ratio: Option<f32>,
+    flag: bool,
+    #[shema(json)]
+    props: prost_wkt_types::Struct,
+    tags: Option<Vec<String>>,
+}
+
+#[test]
+fn should_verify_sqlite_code() {
+    assert_eq!(
+        CacheEvent::SHEMA_SQLITE_DDL,
+        r#"CREATE TABLE IF NOT EXISTS "cache_event" (
+    "client_id" TEXT NOT NULL,
+    "client_time" TEXT NOT NULL,
+    "user_id" TEXT,
+    "count" INTEGER NOT NULL,
+    "big" TEXT NOT NULL,
+    "ratio" REAL,
+    "flag" INTEGER NOT NULL,
+    "props" TEXT NOT NULL,
+    "tags" TEXT
+);
+CREATE INDEX IF NOT EXISTS "cache_event_client_id_idx" ON "cache_event" ("client_id");"#);
+    assert_eq!(
+        CacheEvent::SHEMA_SQLITE_INSERT,
+        r#"INSERT INTO "cache_event" ("client_id", "client_time", "user_id", "count", "big", "ratio", "flag", "props", "tags") VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#
+    );
+
+    let connection = rusqlite::Connection::open_in_memory().expect("to open sqlite");
+    connection.execute_batch(CacheEvent::SHEMA_SQLITE_DDL).expect("to create table");
+
+    let mut event = CacheEvent {
+        client_id: "id".to_owned(),
+        client_time: time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2020, 31).unwrap(), time::Time::from_hms_milli(1, 2, 3, 456).unwrap()),
+        user_id: None,
+        count: 5,
+        big: u64::MAX,
+        ratio: Some(0.5),
+        flag: true,
+        props: [("key".to_owned(), "value".to_owned())].into_iter().collect(),
+        tags: Some(vec!["tag".to_owned()]),
+    };
+    assert_eq!(event.shema_sqlite_insert(&connection).expect("to insert"), 1);
+
+    let row = connection.query_row("SELECT * FROM cache_event", [], |row| Ok((
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, Option<String>>(2)?,
+        row.get::<_, i64>(3)?,
+        row.get::<_, String>(4)?,
+        row.get::<_, Option<f64>>(5)?,
+        row.get::<_, bool>(6)?,
+        row.get::<_, String>(7)?,
+        row.get::<_, Option<String>>(8)?,
+    ))).expect("to read row");
+    assert_eq!(row, (
+        "id".to_owned(),
+        "2020-01-31T01:02:03.456Z".to_owned(),
+        None,
+        5,
+        u64::MAX.to_string(),
+        Some(0.5),
+        true,
+        r#"{"key":"value"}"#.to_owned(),
+        Some(r#"["tag"]"#.to_owned()),
+    ));
+
+    event.count = u64::MAX;
+    event.shema_sqlite_insert(&connection).expect_err("to fail on overflow");
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(clickhouse)]
+pub(crate) struct ClickhouseEvent {
+    #[shema(index)]
+    ///Device's serial number
+    serial: String,
+    #[shema(index, firehose_date_index)]
+    received_at: time::OffsetDateTime,
+    firmware: Option<String>,
+    #[shema(json)]
+    settings: Option<prost_wkt_types::Struct>,
+    #[shema(enumeration)]
+    model: prost_wkt_types::Struct,
+    battery: u8,
+    voltage: i16,
+    temperature: f32,
+    charging: bool,
+    history: Vec<String>,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(clickhouse)]
+pub(crate) struct ClickhouseNativeEvent {
+    #[shema(native)]
+    tags: Vec<String>,
+    #[shema(native)]
+    ids: Option<std::collections::HashSet<u64>>,
+    #[shema(native)]
+    counters: std::collections::HashMap<String, i32>,
+    #[shema(native)]
+    weights: Option<std::collections::HashMap<String, f64>>,
+}
+
+#[test]
+fn should_verify_clickhouse_ddl() {
+    assert_eq!(
+        ClickhouseEvent::SHEMA_CLICKHOUSE_DDL,
+        r#"CREATE TABLE IF NOT EXISTS `clickhouse_event` (
+    `serial` String COMMENT 'Device\'s serial number',
+    `received_at` DateTime64(6, 'UTC'),
+    `firmware` Nullable(String),
+    `settings` Nullable(String),
+    `model` LowCardinality(String),
+    `battery` UInt8,
+    `voltage` Int16,
+    `temperature` Float32,
+    `charging` Bool,
+    `history` String
+)
+ENGINE = MergeTree
+PARTITION BY toYYYYMMDD(`received_at`)
+ORDER BY (`serial`, `received_at`)"#);
+
+    assert_eq!(
+        ClickhouseNativeEvent::SHEMA_CLICKHOUSE_DDL,
+        r#"CREATE TABLE IF NOT EXISTS `clickhouse_native_event` (
+    `tags` Array(String),
+    `ids` Array(UInt64),
+    `counters` Map(String, Int32),
+    `weights` Map(String, Float64)
+)
+ENGINE = MergeTree
+ORDER BY tuple()"#);
+}
+
+#[allow(unused)]
+pub(crate) enum Status {
+    Open,
+    Closed,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(avro)]
+pub(crate) struct AvroLocation {
+    lat: f64,
+    ///Human readable name
+    name: Option<String>,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(avro)]
+pub(crate) struct AvroEvent {
+    ///Client's id
+    client_id: String,
+    client_time: time::OffsetDateTime,
+    count: u32,
+    #[shema(decimal)]
+    big: u64,
+    #[shema(json)]
+    props: Option<prost_wkt_types::Struct>,
+    #[shema(enumeration, symbols = "Open, Closed")]
+    status: Status,
+    #[shema(enumeration, symbols = "Open, Closed")]
+    previous_status: Option<Status>,
+    #[shema(enumeration)]
+    kind: prost_wkt_types::Struct,
+    #[shema(nested)]
+    location: AvroLocation,
+    #[shema(nested)]
+    home: Option<AvroLocation>,
+}
+
+#[test]
+fn should_verify_avro_schema() {
+    assert_eq!(
+        AvroEvent::SHEMA_AVRO_SCHEMA,
+        r#"{
+  "type": "record",
+  "name": "AvroEvent",
+  "fields": [
+    {
+      "name": "client_id",
+      "type": "string",
+      "doc": "Client's id"
+    },
+    {
+      "name": "client_time",
+      "type": {
+        "type": "long",
+        "logicalType": "timestamp-micros"
+      }
+    },
+    {
+      "name": "count",
+      "type": "long"
+    },
+    {
+      "name": "big",
+      "type": {
+        "type": "bytes",
+        "logicalType": "decimal",
+        "precision": 20,
+        "scale": 0
+      }
+    },
+    {
+      "name": "props",
+      "type": [
+        "null",
+        "string"
+      ],
+      "default": null
+    },
+    {
+      "name": "status",
+      "type": {
+        "type": "enum",
+        "name": "Status",
+        "symbols": [
+          "Open",
+          "Closed"
+        ]
+      }
+    },
+    {
+      "name": "previous_status",
+      "type": [
+        "null",
+        "Status"
+      ],
+      "default": null
+    },
+    {
+      "name": "kind",
+      "type": "string"
+    },
+    {
+      "name": "location",
+      "type": {
+        "type": "record",
+        "name": "AvroLocation",
+        "fields": [
+          {
+            "name": "lat",
+            "type": "double"
+          },
+          {
+            "name": "name",
+            "type": [
+              "null",
+              "string"
+            ],
+            "doc": "Human readable name",
+            "default": null
+          }
+        ]
+      }
+    },
+    {
+      "name": "home",
+      "type": [
+        "null",
+        "AvroLocation"
+      ],
+      "default": null
+    }
+  ]
+}"#);
+    serde_json::from_str::<serde_json::Value>(AvroEvent::SHEMA_AVRO_SCHEMA).expect("valid json");
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(json_schema)]
+pub(crate) struct JsonSchemaLocation {
+    lat: f64,
+    ///Human readable name
+    name: Option<String>,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(json_schema)]
+pub(crate) struct JsonSchemaEvent {
+    ///Time of event
+    client_time: time::OffsetDateTime,
+    user_id: Option<String>,
+    count: u32,
+    #[shema(decimal)]
+    big: u64,
+    #[shema(json)]
+    props: Option<prost_wkt_types::Struct>,
+    #[shema(enumeration, symbols = "Open, Closed")]
+    previous_status: Option<Status>,
+    #[shema(nested)]
+    home: Option<JsonSchemaLocation>,
+}
+
+#[test]
+fn should_verify_json_schema() {
+    assert_eq!(
+        JsonSchemaEvent::SHEMA_JSON_SCHEMA,
+        r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "json_schema_event",
+  "type": "object",
+  "properties": {
+    "client_time": {
+      "type": "string",
+      "format": "date-time",
+      "description": "Time of event"
+    },
+    "user_id": {
+      "type": [
+        "string",
+        "null"
+      ]
+    },
+    "count": {
+      "type": "integer",
+      "minimum": 0,
+      "maximum": 4294967295
+    },
+    "big": {
+      "type": "integer",
+      "minimum": 0,
+      "maximum": 18446744073709551615
+    },
+    "props": {},
+    "previous_status": {
+      "enum": [
+        "Open",
+        "Closed",
+        null
+      ]
+    },
+    "home": {
+      "anyOf": [
+        {
+          "title": "json_schema_location",
+          "type": "object",
+          "properties": {
+            "lat": {
+              "type": "number"
+            },
+            "name": {
+              "type": [
+                "string",
+                "null"
+              ],
+              "description": "Human readable name"
+            }
+          },
+          "required": [
+            "lat"
+          ]
+        },
+        {
+          "type": "null"
+        }
+      ]
+    }
+  },
+  "required": [
+    "client_time",
+    "count",
+    "big"
+  ]
+}"#);
+    serde_json::from_str::<serde_json::Value>(JsonSchemaEvent::SHEMA_JSON_SCHEMA).expect("valid json");
+}
+
+#[derive(Shema)]
+#[shema(arrow_code)]
+pub(crate) struct ArrowLocation {
+    lat: f64,
+    ///Human readable name
+    name: Option<String>,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(arrow_code, timestamp = "micros")]
+pub(crate) struct ArrowEvent {
+    #[shema(index)]
+    client_id: String,
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    user_id: Option<String>,
+    count: u32,
+    #[shema(decimal)]
+    big: u64,
+    #[shema(json)]
+    props: prost_wkt_types::Struct,
+    #[shema(native)]
+    tags: Vec<String>,
+    #[shema(native)]
+    counters: std::collections::HashMap<String, i64>,
+    #[shema(nested)]
+    home: Option<ArrowLocation>,
+}
+
+#[test]
+fn should_verify_arrow_record_batch() {
+    use arrow_array::Array;
+    use arrow_schema::{DataType, TimeUnit};
+
+    let schema = ArrowEvent::shema_arrow_schema();
+    let types = schema.fields().iter().map(|field| (field.name().as_str(), field.data_type().clone(), field.is_nullable())).collect::<Vec<_>>();
+    assert_eq!(types[..6], [
+        ("client_time", DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), false),
+        ("user_id", DataType::Utf8, true),
+        ("count", DataType::UInt32, false),
+        ("big", DataType::Decimal128(20, 0), false),
+        ("props", DataType::Utf8, false),
+        ("tags", DataType::List(arrow_schema::Field::new("element", DataType::Utf8, false).into()), false),
+    ]);
+    assert_eq!(types[6].0, "counters");
+    assert!(matches!(types[6].1, DataType::Map(_, false)));
+    assert_eq!(types[7].0, "home");
+    assert_eq!(types[7].1, DataType::Struct(ArrowLocation::shema_arrow_fields().into()));
+    assert!(types[7].2);
+    assert_eq!(types.len(), 8);
+
+    let client_time = time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+    let records = [
+        ArrowEvent {
+            client_id: "client".to_owned(),
+            client_time,
+            user_id: Some("user".to_owned()),
+            count: 1,
+            big: u64::MAX,
+            props: [("key".to_owned(), "value".to_owned())].into(),
+            tags: vec!["a".to_owned(), "b".to_owned()],
+            counters: [("visits".to_owned(), 2)].into(),
+            home: Some(ArrowLocation {
+                lat: 1.5,
+                name: None,
+            }),
+        },
+        ArrowEvent {
+            client_id: "client".to_owned(),
+            client_time,
+            user_id: None,
+            count: 2,
+            big: 0,
+            props: Default::default(),
+            tags: Vec::new(),
+            counters: Default::default(),
+            home: None,
+        },
+    ];
+    let batch = ArrowEvent::shema_arrow_record_batch(&records).expect("to build record batch");
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.schema().as_ref(), &schema);
+
+    let client_time_column = batch.column(0).as_any().downcast_ref::<arrow_array::TimestampMicrosecondArray>().unwrap();
+    assert_eq!(client_time_column.value(0), 1_700_000_000_000_000);
+    let user_id = batch.column(1).as_any().downcast_ref::<arrow_array::StringArray>().unwrap();
+    assert_eq!(user_id.value(0), "user");
+    assert!(user_id.is_null(1));
+    let big = batch.column(3).as_any().downcast_ref::<arrow_array::Decimal128Array>().unwrap();
+    assert_eq!(big.value(0), u64::MAX as i128);
+    let props = batch.column(4).as_any().downcast_ref::<arrow_array::StringArray>().unwrap();
+    assert_eq!(props.value(0), r#"{"key":"value"}"#);
+    let tags = batch.column(5).as_any().downcast_ref::<arrow_array::ListArray>().unwrap();
+    assert_eq!(tags.value_length(0), 2);
+    assert_eq!(tags.value_length(1), 0);
+    let counters = batch.column(6).as_any().downcast_ref::<arrow_array::MapArray>().unwrap();
+    assert_eq!(counters.value_length(0), 1);
+    let home = batch.column(7).as_any().downcast_ref::<arrow_array::StructArray>().unwrap();
+    assert!(home.is_valid(0));
+    assert!(home.is_null(1));
+    let lat = home.column(0).as_any().downcast_ref::<arrow_array::Float64Array>().unwrap();
+    assert_eq!(lat.value(0), 1.5);
+    assert!(home.column(1).is_null(0));
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(bigquery_schema)]
+pub(crate) struct BigQueryLocation {
+    lat: f64,
+    ///Human readable name
+    name: Option<String>,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(bigquery_schema)]
+pub(crate) struct BigQueryEvent {
+    #[shema(index)]
+    client_id: String,
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    user_id: Option<String>,
+    count: u32,
+    #[shema(decimal)]
+    big: u64,
+    #[shema(json)]
+    props: prost_wkt_types::Struct,
+    #[shema(native)]
+    tags: Vec<String>,
+    #[shema(native)]
+    counters: std::collections::HashMap<String, i64>,
+    #[shema(nested)]
+    home: Option<BigQueryLocation>,
+}
+
+#[test]
+fn should_verify_bigquery_schema() {
+    assert_eq!(BigQueryEvent::SHEMA_BIGQUERY_SCHEMA, r#"[
+  {
+    "name": "client_id",
+    "type": "STRING",
+    "mode": "REQUIRED"
+  },
+  {
+    "name": "client_time",
+    "type": "TIMESTAMP",
+    "mode": "REQUIRED"
+  },
+  {
+    "name": "user_id",
+    "type": "STRING",
+    "mode": "NULLABLE"
+  },
+  {
+    "name": "count",
+    "type": "INT64",
+    "mode": "REQUIRED"
+  },
+  {
+    "name": "big",
+    "type": "NUMERIC",
+    "mode": "REQUIRED"
+  },
+  {
+    "name": "props",
+    "type": "JSON",
+    "mode": "REQUIRED"
+  },
+  {
+    "name": "tags",
+    "type": "STRING",
+    "mode": "REPEATED"
+  },
+  {
+    "name": "counters",
+    "type": "JSON",
+    "mode": "REQUIRED"
+  },
+  {
+    "name": "home",
+    "type": "RECORD",
+    "mode": "NULLABLE",
+    "fields": [
+      {
+        "name": "lat",
+        "type": "FLOAT64",
+        "mode": "REQUIRED"
+      },
+      {
+        "name": "name",
+        "type": "STRING",
+        "mode": "NULLABLE",
+        "description": "Human readable name"
+      }
+    ]
+  }
+]"#);
+    assert_eq!(BigQueryEvent::SHEMA_BIGQUERY_TIME_PARTITIONING, r#"{
+  "type": "DAY",
+  "field": "client_time"
+}"#);
+    assert_eq!(BigQueryEvent::SHEMA_BIGQUERY_CLUSTERING, r#"{
+  "fields": [
+    "client_id"
+  ]
+}"#);
+    serde_json::from_str::<serde_json::Value>(BigQueryEvent::SHEMA_BIGQUERY_SCHEMA).expect("valid json");
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(terraform)]
+pub(crate) struct TerraformLocation {
+    lat: f64,
+    name: Option<String>,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(terraform, athena_location = "s3://bucket/terraform_event/")]
+pub(crate) struct TerraformEvent {
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    #[shema(index)]
+    ///Client's id
+    client_id: String,
+    ///User's 'name'
+    name: Option<String>,
+    #[shema(nested)]
+    location: TerraformLocation,
+    #[shema(json)]
+    props: prost_wkt_types::Struct,
+}
+
+#[test]
+fn should_verify_terraform() {
+    assert_eq!(
+        TerraformEvent::SHEMA_TERRAFORM_GLUE_TABLE,
+        r#"table_type = "EXTERNAL_TABLE"
+
+partition_keys {
+  name    = "year"
+  type    = "string"
+  comment = "Extracted from 'client_time'"
+}
+
+partition_keys {
+  name    = "month"
+  type    = "string"
+  comment = "Extracted from 'client_time'"
+}
+
+partition_keys {
+  name    = "day"
+  type    = "string"
+  comment = "Extracted from 'client_time'"
+}
+
+partition_keys {
+  name    = "client_id"
+  type    = "string"
+  comment = "Client's id"
+}
+
+storage_descriptor {
+  location      = "s3://bucket/terraform_event/"
+  input_format  = "org.apache.hadoop.hive.ql.io.parquet.MapredParquetInputFormat"
+  output_format = "org.apache.hadoop.hive.ql.io.parquet.MapredParquetOutputFormat"
+
+  ser_de_info {
+    serialization_library = "org.apache.hadoop.hive.ql.io.parquet.serde.ParquetHiveSerDe"
+  }
+
+  columns {
+    name = "client_time"
+    type = "timestamp"
+  }
+
+  columns {
+    name    = "name"
+    type    = "string"
+    comment = "User's 'name'"
+  }
+
+  columns {
+    name = "location"
+    type = "struct<lat:double,name:string>"
+  }
+
+  columns {
+    name = "props"
+    type = "string"
+  }
+}"#
+    );
+    assert_eq!(
+        TerraformEvent::SHEMA_TERRAFORM_FIREHOSE_PROCESSING,
+        r#"processing_configuration {
+  enabled = true
+
+  processors {
+    type = "MetadataExtraction"
+
+    parameters {
+      parameter_name  = "MetadataExtractionQuery"
+      parameter_value = "{year:(.client_time|split(\"-\")[0]),month:(.client_time|split(\"-\")[1]),day:(.client_time|split(\"-\")[2]|split(\"T\")[0]),client_id:.client_id}"
+    }
+
+    parameters {
+      parameter_name  = "JsonParsingEngine"
+      parameter_value = "JQ-1.6"
+    }
+  }
+}"#
+    );
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(terraform, athena_location = "s3://bucket/date_event/")]
+pub(crate) struct DateEvent {
+    #[shema(firehose_date_index = "month")]
+    client_time: time::OffsetDateTime,
+    name: String,
+}
+
+#[test]
+fn should_verify_terraform_processing_of_date_index() {
+    assert!(DateEvent::SHEMA_TERRAFORM_GLUE_TABLE.contains("partition_keys {\n  name    = \"month\""));
+    assert!(DateEvent::SHEMA_TERRAFORM_FIREHOSE_PROCESSING.contains(r#"parameter_value = "{year:(.client_time|split(\"-\")[0]),month:(.client_time|split(\"-\")[1])}""#));
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(firehose_partition_code, athena_ddl, athena_projection = "2020,2100", bigquery_schema)]
+pub(crate) struct HourlyEvent {
+    #[shema(index, firehose_date_index = "hour")]
+    client_time: time::OffsetDateTime,
+    #[shema(index)]
+    client_id: String,
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(firehose_partition_code, clickhouse)]
+pub(crate) struct MonthlyEvent {
+    #[shema(index, firehose_date_index = "month")]
+    client_time: time::OffsetDateTime,
+}
+
+#[test]
+fn should_verify_firehose_date_granularity() {
+    let client_time = time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2020, 31).unwrap(), time::Time::from_hms(1, 2, 3).unwrap());
+    let event = HourlyEvent {
+        client_time,
+        client_id: "id".to_owned(),
+    };
+    assert_eq!(event.shema_firehose_partition_keys(), (2020, 1, 31, 1, "id".to_owned()));
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), "year=2020/month=01/day=31/hour=01/client_id=id/");
+    assert_eq!(HourlyEvent::SHEMA_FIREHOSE_JQ_QUERY, r#"{year:(.client_time|split("-")[0]),month:(.client_time|split("-")[1]),day:(.client_time|split("-")[2]|split("T")[0]),hour:(.client_time|split("T")[1]|split(":")[0]),client_id:.client_id}"#);
+    assert_eq!(HourlyEvent::SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE, "year=!{partitionKeyFromQuery:year}/month=!{partitionKeyFromQuery:month}/day=!{partitionKeyFromQuery:day}/hour=!{partitionKeyFromQuery:hour}/client_id=!{partitionKeyFromQuery:client_id}/");
+    assert!(HourlyEvent::SHEMA_ATHENA_DDL.contains("  `hour` string COMMENT 'Extracted from \\'client_time\\'',\n"));
+    assert!(HourlyEvent::SHEMA_ATHENA_DDL.contains("  'projection.hour.type'='integer',\n  'projection.hour.range'='0,23',\n  'projection.hour.digits'='2',\n"));
+    assert!(HourlyEvent::SHEMA_BIGQUERY_TIME_PARTITIONING.contains(r#""type": "HOUR""#));
+
+    let event = MonthlyEvent {
+        client_time,
+    };
+    assert_eq!(event.shema_firehose_partition_keys(), (2020, 1));
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), "year=2020/month=01/");
+    assert!(MonthlyEvent::SHEMA_CLICKHOUSE_DDL.contains("\nPARTITION BY toYYYYMM(`client_time`)\n"));
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(firehose_schema, firehose_partition_code, athena_ddl, athena_location = "s3://bucket/ordered_event", athena_projection = "2020,2100")]
+#[shema(partition_order = "client_id, date", s3_prefix_style = "values")]
+pub(crate) struct OrderedEvent {
+    #[shema(index, firehose_date_index = "month")]
+    client_time: time::OffsetDateTime,
+    name: String,
+    #[shema(index)]
+    client_id: String,
+}
+
+#[test]
+fn should_verify_partition_order_and_prefix_style() {
+    let event = OrderedEvent {
+        client_time: time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2020, 31).unwrap(), time::Time::from_hms(1, 2, 3).unwrap()),
+        name: "name".to_owned(),
+        client_id: "id".to_owned(),
+    };
+    assert_eq!(event.shema_firehose_partition_keys(), ("id".to_owned(), 2020, 1));
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), "id/2020/01/");
+    assert_eq!(OrderedEvent::SHEMA_FIREHOSE_JQ_QUERY, r#"{client_id:.client_id,year:(.client_time|split("-")[0]),month:(.client_time|split("-")[1])}"#);
+    assert_eq!(OrderedEvent::SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE, "!{partitionKeyFromQuery:client_id}/!{partitionKeyFromQuery:year}/!{partitionKeyFromQuery:month}/");
+
+    let schema = serde_json::from_str::<serde_json::Value>(OrderedEvent::SHEMA_FIREHOSE_SCHEMA).expect("valid json");
+    let partition_keys = schema["partition_keys"].as_array().unwrap().iter().map(|key| key["name"].as_str().unwrap()).collect::<Vec<_>>();
+    assert_eq!(partition_keys, ["client_id", "year", "month"]);
+
+    assert!(OrderedEvent::SHEMA_ATHENA_DDL.contains("PARTITIONED BY (\n  `client_id` string,\n  `year` string"));
+    assert!(OrderedEvent::SHEMA_ATHENA_DDL.contains("  'storage.location.template'='s3://bucket/ordered_event/${client_id}/${year}/${month}/'\n"));
+}
+
+#[derive(Shema)]
+#[shema(firehose_schema, firehose_partition_code)]
+pub(crate) struct PartitionEvent {
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    #[shema(index)]
+    client_id: String,
+}
+
+#[test]
+fn should_verify_firehose_prefix_template() {
+    let event = PartitionEvent {
+        client_time: time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2020, 31).unwrap(), time::Time::from_hms(1, 2, 3).unwrap()),
+        client_id: "id".to_owned(),
+    };
+
+    //Delivery stream's prefix must match the one generated locally
+    assert_eq!(PartitionEvent::SHEMA_FIREHOSE_JQ_QUERY, r#"{year:(.client_time|split("-")[0]),month:(.client_time|split("-")[1]),day:(.client_time|split("-")[2]|split("T")[0]),client_id:.client_id}"#);
+    assert_eq!(PartitionEvent::SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE, "year=!{partitionKeyFromQuery:year}/month=!{partitionKeyFromQuery:month}/day=!{partitionKeyFromQuery:day}/client_id=!{partitionKeyFromQuery:client_id}/");
+    let prefix = [("year", "2020"), ("month", "01"), ("day", "31"), ("client_id", "id")].iter().fold(PartitionEvent::SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE.to_owned(), |prefix, (key, value)| {
+        prefix.replace(&format!("!{{partitionKeyFromQuery:{key}}}"), value)
+    });
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), prefix);
+}
+
+#[test]
+fn should_validate_firehose_partition_values() {
+    let mut event = PartitionEvent {
+        client_time: time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2020, 31).unwrap(), time::Time::from_hms(1, 2, 3).unwrap()),
+        client_id: " ".to_owned(),
+    };
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(PartitionEventPartitionError::Empty("client_id")));
+    assert!(!event.shema_is_firehose_s3_path_prefix_valid());
+
+    event.client_id = "..".to_owned();
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(PartitionEventPartitionError::Relative("client_id")));
+    event.client_id = "a/b".to_owned();
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(PartitionEventPartitionError::Separator("client_id")));
+    event.client_id = "a=b".to_owned();
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(PartitionEventPartitionError::Separator("client_id")));
+    event.client_id = "a:b".to_owned();
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(PartitionEventPartitionError::EscapedChar("client_id")));
+    event.client_id = "100%".to_owned();
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(PartitionEventPartitionError::EscapedChar("client_id")));
+
+    event.client_id = "é".repeat(257);
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(PartitionEventPartitionError::TooLong("client_id")));
+    assert_eq!(PartitionEventPartitionError::TooLong("client_id").to_string(), "Partition key 'client_id' exceeds 256 characters");
+    event.client_id = "é".repeat(256);
+    assert!(event.shema_is_firehose_s3_path_prefix_valid());
+
+    //Valid value is written as it is, the same way as Firehose does
+    event.client_id = "a.b-é".to_owned();
+    assert!(event.shema_is_firehose_s3_path_prefix_valid());
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), "year=2020/month=01/day=31/client_id=a.b-é/");
+}
+
+#[allow(unused)]
+#[derive(serde_derive::Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Platform {
+    Ios,
+    AndroidTv,
+}
+
+#[derive(Shema)]
+#[shema(firehose_json)]
+pub(crate) struct FirehoseJsonLocation {
+    lat: f64,
+    name: Option<String>,
+}
+
+#[derive(Shema)]
+#[shema(firehose_json)]
+pub(crate) struct FirehoseJsonEvent<'a> {
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    #[shema(index)]
+    client_id: String,
+    user_id: Option<String>,
+    #[shema(json)]
+    #[shema(rename = "extra")]
+    extras: Option<prost_wkt_types::Struct>,
+    #[shema(json)]
+    props: prost_wkt_types::Struct,
+    #[shema(enumeration)]
+    false_enum: prost_wkt_types::Struct,
+    #[shema(enumeration)]
+    platform: Platform,
+    #[shema(enumeration)]
+    source: Source,
+    #[shema(nested)]
+    location: FirehoseJsonLocation,
+    long: i64,
+    float: f32,
+    boolean: bool,
+    #[shema(rename = "stroka")]
+    strka: &'a str,
+    array: Vec<String>,
+}
+
+#[test]
+fn should_verify_firehose_json() {
+    let event = FirehoseJsonEvent {
+        client_time: time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2020, 31).unwrap(), time::Time::from_hms(1, 2, 3).unwrap()),
+        client_id: "id".to_owned(),
+        user_id: None,
+        extras: None,
+        props: [("key".to_owned(), "value".to_owned())].into(),
+        false_enum: prost_wkt_types::Struct::new(),
+        platform: Platform::AndroidTv,
+        source: Source::Custom("app".to_owned()),
+        location: FirehoseJsonLocation {
+            lat: 1.5,
+            name: None,
+        },
+        long: 4,
+        float: 5.0,
+        boolean: false,
+        strka: "test",
+        array: Vec::new(),
+    };
+    let mut json = Vec::new();
+    event.shema_firehose_json(&mut json).expect("to write json");
+    assert_eq!(
+        String::from_utf8(json).unwrap(),
+        concat!(
+            r#"{"client_time":"2020-01-31T01:02:03Z","client_id":"id","user_id":null,"extra":null,"props":"{\"key\":\"value\"}","false_enum":"{}","platform":"android_tv","source":"{\"Custom\":\"app\"}","#,
+            r#""location":{"lat":1.5,"name":null},"long":4,"float":5.0,"boolean":false,"stroka":"test","array":"[]"}"#,
+            "\n"
+        )
+    );
+}
+
+#[derive(Shema)]
+#[shema(firehose_partition_code, firehose_json)]
+pub(crate) struct EnumPartitionEvent {
+    #[shema(index, enumeration)]
+    platform: Platform,
+    client_id: String,
+}
+
+#[test]
+fn should_verify_enum_partition_key_of_firehose_json() {
+    let event = EnumPartitionEvent {
+        platform: Platform::AndroidTv,
+        client_id: "id".to_owned(),
+    };
+    let mut json = Vec::new();
+    event.shema_firehose_json(&mut json).expect("to write json");
+    assert_eq!(String::from_utf8(json.clone()).unwrap(), "{\"platform\":\"android_tv\",\"client_id\":\"id\"}\n");
+
+    //Firehose extracts partition key as `.platform` out of written record
+    assert_eq!(EnumPartitionEvent::SHEMA_FIREHOSE_JQ_QUERY, "{platform:.platform}");
+    let json: serde_json::Value = serde_json::from_slice(&json).expect("to parse json");
+    let platform = json["platform"].as_str().expect("to have string platform");
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), format!("platform={platform}/"));
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(firehose_schema, firehose_partition_code)]
+pub(crate) struct TypedPartitionEvent {
+    #[shema(index)]
+    region: Option<String>,
+    #[shema(index, enumeration)]
+    platform: Platform,
+    #[shema(index)]
+    is_test: bool,
+    #[shema(index)]
+    shard: u16,
+    #[shema(index)]
+    version: Option<u32>,
+}
+
+#[test]
+fn should_verify_typed_partition_keys() {
+    let mut event = TypedPartitionEvent {
+        region: None,
+        platform: Platform::AndroidTv,
+        is_test: false,
+        shard: 7,
+        version: Some(2),
+    };
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), "region=__HIVE_DEFAULT_PARTITION__/platform=android_tv/is_test=false/shard=7/version=2/");
+    assert!(event.shema_is_firehose_s3_path_prefix_valid());
+
+    event.region = Some("eu/west".to_owned());
+    event.version = None;
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), "region=eu/west/platform=android_tv/is_test=false/shard=7/version=__HIVE_DEFAULT_PARTITION__/");
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(TypedPartitionEventPartitionError::Separator("region")));
+    event.region = Some(String::new());
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(TypedPartitionEventPartitionError::Empty("region")));
+
+    assert_eq!(
+        TypedPartitionEvent::SHEMA_FIREHOSE_JQ_QUERY,
+        r#"{region:(if .region == null then "__HIVE_DEFAULT_PARTITION__" else (.region|tostring) end),platform:.platform,is_test:(.is_test|tostring),shard:(.shard|tostring),version:(if .version == null then "__HIVE_DEFAULT_PARTITION__" else (.version|tostring) end)}"#
+    );
+}
+
+#[allow(unused)]
+#[derive(serde_derive::Serialize, Clone)]
+pub(crate) enum Source {
+    Web,
+    Custom(String),
+}
+
+#[derive(Shema)]
+#[shema(firehose_partition_code)]
+pub(crate) struct DataEnumPartitionEvent {
+    #[shema(index, enumeration)]
+    source: Source,
+    #[shema(index, enumeration)]
+    previous_source: Option<Source>,
+}
+
+#[test]
+fn should_validate_enum_partition_keys() {
+    let mut event = DataEnumPartitionEvent {
+        source: Source::Web,
+        previous_source: None,
+    };
+    assert!(event.shema_is_firehose_s3_path_prefix_valid());
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), "source=Web/previous_source=__HIVE_DEFAULT_PARTITION__/");
+
+    //Variant with data is serialized as object, which cannot be partition value
+    event.previous_source = Some(Source::Custom("app".to_owned()));
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(DataEnumPartitionEventPartitionError::NotString("previous_source")));
+    assert_eq!(event.shema_firehose_s3_path_prefix().to_string(), r#"source=Web/previous_source={"Custom":"app"}/"#);
+
+    event.source = Source::Custom(String::new());
+    assert_eq!(event.shema_validate_firehose_s3_path_prefix(), Err(DataEnumPartitionEventPartitionError::NotString("source")));
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(firehose_partition_code, parquet_code, parquet_sink)]
+pub(crate) struct SinkEvent {
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    #[shema(index)]
+    client_id: String,
+    count: u32,
+}
+
+#[test]
+fn should_write_partitioned_parquet_sink() {
+    let root = std::env::temp_dir().join(format!("shema-sink-{}", std::process::id()));
+    let client_time = time::OffsetDateTime::new_utc(time::Date::from_ordinal_date(2020, 31).unwrap(), time::Time::from_hms(1, 2, 3).unwrap());
+    let event = |client_id: &str, count: u32| SinkEvent {
+        client_time,
+        client_id: client_id.to_owned(),
+        count,
+    };
+    let props = std::sync::Arc::new(parquet::file::properties::WriterProperties::builder().build());
+    let mut sink = SinkEventParquetSink::new(&root, props.clone()).with_max_rows(2);
+
+    sink.write(&[event("a", 1), event("b", 2)]).expect("to write");
+    //Partition `a` reaches limit in the middle of write and the rest goes into new file
+    sink.write(&[event("a", 3), event("a", 4)]).expect("to write");
+    sink.write(&[event("a", 5)]).expect("to write");
+    let files = sink.close().expect("to close");
+
+    let mut partitions = files.iter().map(|path| {
+        let partition = path.parent().unwrap().strip_prefix(&root).unwrap().to_str().unwrap().to_owned();
+        (partition, parquet_file_rows(path))
+    }).collect::<Vec<_>>();
+    partitions.sort();
+    assert_eq!(partitions, [
+        ("year=2020/month=01/day=31/client_id=a".to_owned(), 2),
+        ("year=2020/month=01/day=31/client_id=a".to_owned(), 2),
+        ("year=2020/month=01/day=31/client_id=b".to_owned(), 1),
+    ]);
+
+    //Single write exceeding limit is split between files
+    let mut sink = SinkEventParquetSink::new(&root, props.clone()).with_max_rows(2);
+    sink.write(&(1..=5).map(|count| event("d", count)).collect::<Vec<_>>()).expect("to write");
+    let files = sink.close().expect("to close");
+    let rows = files.iter().map(|path| parquet_file_rows(path)).collect::<Vec<_>>();
+    assert_eq!(rows, [2, 2, 1]);
+
+    //Any written row group exceeds single byte
+    let mut sink = SinkEventParquetSink::new(&root, props.clone()).with_max_bytes(1);
+    sink.write(&[event("c", 1)]).expect("to write");
+    sink.write(&[event("c", 2)]).expect("to write");
+    assert_eq!(sink.close().expect("to close").len(), 2);
+
+    //Partition value must not escape root directory
+    let mut sink = SinkEventParquetSink::new(&root, props.clone());
+    let error = sink.write(&[event("e", 1), event("..", 2)]).expect_err("to reject relative path");
+    assert_eq!(error.to_string(), "Parquet error: Partition key 'client_id' is relative path");
+    assert!(sink.close().expect("to close").is_empty());
+    assert!(!root.join("year=2020/month=01/day=31/client_id=e").exists());
+
+    std::fs::remove_dir_all(&root).expect("to remove sink's directory");
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(runtime)]
+pub(crate) struct RuntimeEvent {
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    ///Client's "unique" identifier
+    #[shema(index, rename = "client")]
+    r#client_id: String,
+    count: Option<u64>,
+    #[shema(enumeration)]
+    platform: Platform,
+}
+
+//Tooling is written against trait only
+fn runtime_columns<T: shema_runtime::Shema>() -> Vec<(&'static str, &'static str)> {
+    T::FIELDS.iter().map(|field| (T::TABLE_NAME, field.name)).collect()
+}
+
+#[test]
+fn should_verify_runtime_shema() {
+    use shema_runtime::{Shema, FieldInfo, FieldKind};
+
+    assert_eq!(<RuntimeEvent as Shema>::TABLE_NAME, "runtime_event");
+    assert_eq!(RuntimeEvent::FIELDS, [
+        FieldInfo {
+            name: "client_time",
+            rust_name: "client_time",
+            kind: FieldKind::TimestampZ,
+            optional: false,
+            index: true,
+            date_index: true,
+            doc: "",
+        },
+        FieldInfo {
+            name: "client",
+            rust_name: "client_id",
+            kind: FieldKind::String,
+            optional: false,
+            index: true,
+            date_index: false,
+            doc: "Client's \"unique\" identifier",
+        },
+        FieldInfo {
+            name: "count",
+            rust_name: "count",
+            kind: FieldKind::ULong,
+            optional: true,
+            index: false,
+            date_index: false,
+            doc: "",
+        },
+        FieldInfo {
+            name: "platform",
+            rust_name: "platform",
+            kind: FieldKind::Enum,
+            optional: false,
+            index: false,
+            date_index: false,
+            doc: "",
+        },
+    ]);
+    assert_eq!(runtime_columns::<RuntimeEvent>(), [
+        ("runtime_event", "client_time"),
+        ("runtime_event", "client"),
+        ("runtime_event", "count"),
+        ("runtime_event", "platform"),
+    ]);
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+//Baseline declares narrower `count` and `ratio`, while `comment` is appended
+#[shema(firehose_schema, schema_baseline = "tests/baseline/baseline_event.json")]
+pub(crate) struct BaselineEvent {
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    #[shema(index)]
+    client_id: String,
+    count: u64,
+    #[shema(nested)]
+    location: Option<Location>,
+    ratio: f64,
+    comment: Option<String>,
+}
+
+#[test]
+fn should_verify_schema_baseline() {
+    let baseline: serde_json::Value = serde_json::from_str(BaselineEvent::SHEMA_BASELINE).expect("valid json");
+    let keys = baseline["partition_keys"].as_array().unwrap().iter().map(|key| key["name"].as_str().unwrap()).collect::<Vec<_>>();
+    assert_eq!(keys, ["year", "month", "day", "client_id"]);
+    let columns = baseline["columns"].as_array().unwrap().iter().map(|column| (column["name"].as_str().unwrap(), column["type"].as_str().unwrap(), column["required"].as_bool().unwrap())).collect::<Vec<_>>();
+    assert_eq!(columns, [
+        ("client_time", "timestamp", true),
+        ("count", "bigint", true),
+        ("location", "struct<lat:double,name:string>", false),
+        ("ratio", "double", true),
+        ("comment", "string", false),
+    ]);
+
+    let stored: serde_json::Value = serde_json::from_str(include_str!("baseline/baseline_event.json")).expect("valid json");
+    assert_eq!(stored["partition_keys"], baseline["partition_keys"]);
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(firehose_schema, firehose_parquet_schema, athena_ddl, postgres, export_dir = "target/shema_export")]
+pub(crate) struct ExportEvent {
+    #[shema(index, firehose_date_index)]
+    client_time: time::OffsetDateTime,
+    #[shema(index)]
+    client_id: String,
+    count: Option<u32>,
+}
+
+#[test]
+fn should_export_outputs() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("target/shema_export");
+    let read = |extension: &str| std::fs::read_to_string(dir.join(format!("{}.{extension}", ExportEvent::SHEMA_TABLE_NAME))).expect("to export output");
+
+    assert_eq!(read("firehose.json"), ExportEvent::SHEMA_FIREHOSE_SCHEMA);
+    assert_eq!(read("baseline.json"), ExportEvent::SHEMA_BASELINE);
+    assert_eq!(read("firehose.jq"), ExportEvent::SHEMA_FIREHOSE_JQ_QUERY);
+    assert_eq!(read("s3_prefix.txt"), ExportEvent::SHEMA_FIREHOSE_S3_PREFIX_TEMPLATE);
+    assert_eq!(read("parquet.txt"), ExportEvent::SHEMA_FIREHOSE_PARQUET_SCHEMA);
+    assert_eq!(read("athena.sql"), ExportEvent::SHEMA_ATHENA_DDL);
+    assert_eq!(read("postgres.sql"), ExportEvent::SHEMA_POSTGRES_DDL);
+    assert!(!dir.join("export_event.sqlite.sql").exists());
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(postgres, avro, json_schema)]
+pub(crate) struct QuotedDocEvent {
+    ///Hash of "#tag"## as in "##"###
+    tag: String,
+}
+
+#[test]
+fn should_verify_raw_string_of_docstring() {
+    assert!(QuotedDocEvent::SHEMA_POSTGRES_DDL.contains(r####"COMMENT ON COLUMN "quoted_doc_event"."tag" IS 'Hash of "#tag"## as in "##"###';"####));
+    assert!(QuotedDocEvent::SHEMA_AVRO_SCHEMA.contains(r####""doc": "Hash of \"#tag\"## as in \"##\"###""####));
+    assert!(QuotedDocEvent::SHEMA_JSON_SCHEMA.contains(r####""description": "Hash of \"#tag\"## as in \"##\"###""####));
+}
+
+#[allow(unused)]
+#[derive(Shema)]
+#[shema(postgres)]
+pub(crate) struct QuotedPostgresEvent {
+    #[shema(index, rename = "say \"hi\"")]
+    greeting: String,
+}
+
+#[test]
+fn should_escape_postgres_identifiers() {
+    assert_eq!(
+        QuotedPostgresEvent::SHEMA_POSTGRES_DDL,
+        r#"CREATE TABLE IF NOT EXISTS "quoted_postgres_event" (
+    "say ""hi""" text NOT NULL
+);
+CREATE INDEX IF NOT EXISTS "quoted_postgres_event_say ""hi""_idx" ON "quoted_postgres_event" ("say ""hi""");"#);
+}
+
+#[derive(Shema)]
+#[shema(sqlite, sqlite_code)]
+pub(crate) struct QuotedSqliteEvent {
+    #[shema(index, rename = "say \"hi\"")]
+    greeting: String,
+}
+
+#[test]
+fn should_escape_sqlite_identifiers() {
+    assert_eq!(
+        QuotedSqliteEvent::SHEMA_SQLITE_DDL,
+        r#"CREATE TABLE IF NOT EXISTS "quoted_sqlite_event" (
+    "say ""hi""" TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS "quoted_sqlite_event_say ""hi""_idx" ON "quoted_sqlite_event" ("say ""hi""");"#);
+    assert_eq!(QuotedSqliteEvent::SHEMA_SQLITE_INSERT, r#"INSERT INTO "quoted_sqlite_event" ("say ""hi""") VALUES (?1)"#);
+
+    let connection = rusqlite::Connection::open_in_memory().expect("to open sqlite");
+    connection.execute_batch(QuotedSqliteEvent::SHEMA_SQLITE_DDL).expect("to create table");
+    let event = QuotedSqliteEvent {
+        greeting: "hello".to_owned(),
+    };
+    assert_eq!(event.shema_sqlite_insert(&connection).expect("to insert"), 1);
+    let greeting = connection.query_row(r#"SELECT "say ""hi""" FROM quoted_sqlite_event"#, [], |row| row.get::<_, String>(0)).expect("to read row");
+    assert_eq!(greeting, "hello");
+}